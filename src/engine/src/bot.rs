@@ -0,0 +1,367 @@
+//! A player which searches where the bricks can go and picks the best board.
+//!
+//! The falling brick and the held one (or the next one for a first hold) are tried in every
+//! rotation and column they can reach from where they are, with the same collisions as the
+//! game. The boards after them are scored by the features of El-Tetris, and the best ones are
+//! tried again with the brick after them.
+
+use std::collections::VecDeque;
+use crate::data::bit_board::BitBoard;
+use crate::data::bricks::Brick;
+use crate::data::game_panel::{GamePanel, LiveBrick, PanelSnapshot, PutNewOneResult};
+use crate::data::replay::GameInput;
+use crate::game::GameCore;
+use crate::tetris::{Movement, Point};
+
+/// How much each feature of a board counts, the board with the highest sum is the best.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Weights {
+    /// Per row cleared.
+    pub lines: f64,
+    /// Per block of the heights of all columns.
+    pub height: f64,
+    /// Per empty block under the top of its column.
+    pub holes: f64,
+    /// Per block of difference between the heights of two columns side by side.
+    pub bumpiness: f64,
+    /// Per block of the wells, a well `n` blocks deep counts `1 + 2 + ... + n`.
+    pub wells: f64,
+}
+
+/// The weights El-Tetris found for lines, height, holes and bumpiness, with a small penalty for
+/// wells.
+pub const DEFAULT_WEIGHTS: Weights = Weights {
+    lines: 0.760666,
+    height: -0.510066,
+    holes: -0.35663,
+    bumpiness: -0.184483,
+    wells: -0.05,
+};
+
+/// How many of the best placements of the falling brick are tried with the brick after it.
+pub const LOOKAHEAD_WIDTH: usize = 5;
+
+/// A place a brick can be dropped to, with the board after it.
+#[derive(Clone, Debug)]
+pub struct Landing {
+    /// The inputs which take the brick there, ending with [`GameInput::Drop`].
+    pub inputs: Vec<GameInput>,
+    /// The blocks after the brick locked and the full rows were cleared.
+    pub board: BitBoard,
+    /// The rows cleared.
+    pub lines: u16,
+}
+
+/// Scores a board, higher is better.
+pub fn evaluate(board: &BitBoard, lines: u16, weights: &Weights) -> f64 {
+    let size = board.get_size();
+    let heights: Vec<i32> = (0..size.width)
+        .map(|x| (0..size.height).find(|&y| board.get(Point::new(x, y))).map_or(0, |y| (size.height - y) as i32))
+        .collect();
+    let holes = (0..size.width)
+        .map(|x| {
+            let top = size.height as i32 - heights[x as usize];
+            (top..size.height as i32).filter(|&y| !board.get(Point::new(x, y as u16))).count() as i32
+        })
+        .sum::<i32>();
+    let bumpiness: i32 = heights.windows(2).map(|pair| (pair[0] - pair[1]).abs()).sum();
+    // the walls are as high as the board
+    let wells: i32 = (0..heights.len())
+        .map(|x| {
+            let left = if x == 0 { size.height as i32 } else { heights[x - 1] };
+            let right = heights.get(x + 1).copied().unwrap_or(size.height as i32);
+            let depth = (left.min(right) - heights[x]).max(0);
+            depth * (depth + 1) / 2
+        })
+        .sum();
+    weights.lines * lines as f64
+        + weights.height * heights.iter().sum::<i32>() as f64
+        + weights.holes * holes as f64
+        + weights.bumpiness * bumpiness as f64
+        + weights.wells * wells as f64
+}
+
+// the panel with the board and the brick, the brick is not on the board yet
+fn load_panel(board: &BitBoard, live_brick: Option<LiveBrick>) -> GamePanel {
+    let mut panel = GamePanel::new(board.get_size());
+    panel.restore(&PanelSnapshot { blocks: board.clone(), current_brick: live_brick });
+    panel
+}
+
+/// Where a new brick appears on the board, `None` if there is no room for it.
+pub fn spawn(board: &BitBoard, brick: &Brick) -> Option<LiveBrick> {
+    let mut panel = load_panel(board, None);
+    match panel.put_new_one(brick) {
+        PutNewOneResult::Success => panel.current_brick,
+        PutNewOneResult::GameOver => None,
+    }
+}
+
+// the inputs which move or rotate the falling brick without dropping it
+const MOVES: [GameInput; 5] = [GameInput::Left, GameInput::Right, GameInput::Rotate, GameInput::RotateCcw, GameInput::Rotate180];
+
+// plays a move or a rotation like the game does, returns whether the brick fits there
+fn try_move(panel: &mut GamePanel, input: GameInput) -> bool {
+    let before = panel.current_brick;
+    match input {
+        GameInput::Left => return panel.move_current_brick(Movement { x: -1, y: 0 }),
+        GameInput::Right => return panel.move_current_brick(Movement { x: 1, y: 0 }),
+        GameInput::Rotate => panel.rotate_current_brick_times(3),
+        GameInput::RotateCcw => panel.rotate_current_brick_times(1),
+        GameInput::Rotate180 => panel.rotate_current_brick_times(2),
+        _ => return false,
+    }
+    panel.current_brick != before
+}
+
+// the board with the blocks locked and the full rows removed, with the count of removed rows
+fn lock(board: &BitBoard, points: &[Point]) -> (BitBoard, u16) {
+    let mut board = board.clone();
+    for point in points {
+        board.set(*point, true);
+    }
+    let size = board.get_size();
+    let is_full = |board: &BitBoard, y: u16| (0..size.width).all(|x| board.get(Point::new(x, y)));
+    let kept: Vec<u16> = (0..size.height).filter(|&y| !is_full(&board, y)).collect();
+    let lines = size.height - kept.len() as u16;
+    if lines == 0 {
+        return (board, 0);
+    }
+    // the rows left fall to the bottom
+    let mut cleared = BitBoard::new(size);
+    for (index, &y) in kept.iter().enumerate() {
+        for x in 0..size.width {
+            cleared.set(Point::new(x, lines + index as u16), board.get(Point::new(x, y)));
+        }
+    }
+    (cleared, lines)
+}
+
+/// Every place the brick can be dropped to from where it is, by moving and rotating it first.
+/// Places reached in several ways are kept once, with the fewest inputs.
+pub fn get_landings(board: &BitBoard, live_brick: LiveBrick) -> Vec<Landing> {
+    let mut panel = load_panel(board, None);
+    let mut landings = Vec::new();
+    let mut places: Vec<Vec<(u16, u16)>> = Vec::new();
+    let mut seen = vec![live_brick];
+    let mut queue = VecDeque::new();
+    queue.push_back((live_brick, Vec::new()));
+    while let Some((state, inputs)) = queue.pop_front() {
+        for input in MOVES.iter() {
+            if !panel.move_current_brick_to(state) {
+                break;
+            }
+            if !try_move(&mut panel, *input) {
+                continue;
+            }
+            let moved = panel.current_brick.unwrap();
+            if !seen.contains(&moved) {
+                seen.push(moved);
+                let mut moved_inputs = inputs.clone();
+                moved_inputs.push(*input);
+                queue.push_back((moved, moved_inputs));
+            }
+        }
+        if !panel.move_current_brick_to(state) {
+            continue;
+        }
+        panel.move_current_brick_to_bottom();
+        let landed = panel.current_brick.unwrap();
+        let points = landed.brick.project_to_new_position(landed.position).points;
+        let mut place: Vec<(u16, u16)> = points.iter().map(|point| (point.x, point.y)).collect();
+        place.sort_unstable();
+        if places.contains(&place) {
+            continue;
+        }
+        places.push(place);
+        let (board, lines) = lock(board, &points);
+        let mut inputs = inputs;
+        inputs.push(GameInput::Drop);
+        landings.push(Landing { inputs, board, lines });
+    }
+    landings
+}
+
+// the score of the best landing of the brick, which has to fit on the board
+fn get_best_score(board: &BitBoard, brick: &Brick, lines: u16, weights: &Weights) -> f64 {
+    match spawn(board, brick) {
+        Some(live_brick) => get_landings(board, live_brick).iter()
+            .map(|landing| evaluate(&landing.board, lines + landing.lines, weights))
+            .fold(f64::NEG_INFINITY, f64::max),
+        None => f64::NEG_INFINITY,
+    }
+}
+
+/// The inputs for the falling brick, holding it first if the held brick goes to a better place.
+/// Empty if there is no falling brick or it cannot go anywhere.
+pub fn find_inputs(core: &GameCore, weights: &Weights) -> Vec<GameInput> {
+    let live_brick = match core.manager.current_brick {
+        Some(live_brick) => live_brick,
+        None => return Vec::new(),
+    };
+    let board = core.manager.snapshot().blocks;
+    // each brick which may be placed now, with the brick which comes after it
+    let mut choices = vec![(Vec::new(), Some(live_brick), core.next_brick)];
+    if core.can_hold {
+        if let Some(held) = core.hold.or(core.next_brick) {
+            // after a first hold the brick after it is not known yet, but the held one can be swapped back in
+            let after = if core.hold.is_some() { core.next_brick } else { Some(live_brick.brick) };
+            choices.push((vec![GameInput::Hold], spawn(&board, &held), after));
+        }
+    }
+    let mut candidates: Vec<(Landing, Option<Brick>, f64)> = Vec::new();
+    for (prefix, start, after) in choices {
+        let start = match start {
+            Some(start) => start,
+            None => continue,
+        };
+        for mut landing in get_landings(&board, start) {
+            let score = evaluate(&landing.board, landing.lines, weights);
+            landing.inputs.splice(0..0, prefix.iter().copied());
+            candidates.push((landing, after, score));
+        }
+    }
+    candidates.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap());
+    candidates.truncate(LOOKAHEAD_WIDTH);
+    candidates.into_iter()
+        .map(|(landing, after, score)| {
+            let score = match after {
+                Some(after) => get_best_score(&landing.board, &after, landing.lines, weights),
+                None => score,
+            };
+            (landing.inputs, score)
+        })
+        // the first of equally good ones, which was the best before looking ahead
+        .fold(None, |best: Option<(Vec<GameInput>, f64)>, (inputs, score)| match best {
+            Some(best) if best.1 >= score => Some(best),
+            _ => Some((inputs, score)),
+        })
+        .map_or(Vec::new(), |(inputs, _)| inputs)
+}
+
+/// Plays a game one input at a time.
+pub struct Bot {
+    weights: Weights,
+    inputs: VecDeque<GameInput>,
+}
+
+impl Bot {
+    /// A bot which scores the boards with the weights.
+    pub fn new(weights: Weights) -> Bot {
+        Bot {
+            weights,
+            inputs: VecDeque::new(),
+        }
+    }
+
+    /// Finds where the brick just put in goes, the inputs left for the last one are dropped.
+    pub fn on_new_brick(&mut self, core: &GameCore) {
+        self.inputs = find_inputs(core, &self.weights).into();
+    }
+
+    /// The next input for the falling brick, if any is left.
+    pub fn next_input(&mut self) -> Option<GameInput> {
+        self.inputs.pop_front()
+    }
+
+    /// Forgets the inputs left, like when a game starts over.
+    pub fn clear(&mut self) {
+        self.inputs.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::*;
+    use crate::data::bricks::{BrickCollection, BrickType};
+    use crate::game::{GameCore, NextResult};
+    use crate::tetris::{GameMode, MasterTiming, Size};
+
+    fn board(rows: &[&str]) -> BitBoard {
+        BitBoard::from_rows(&rows.iter().map(|row| row.to_string()).collect::<Vec<String>>()).unwrap()
+    }
+
+    #[test]
+    fn evaluate_board() {
+        let weights = Weights { lines: 100.0, height: 1.0, holes: 10.0, bumpiness: 1000.0, wells: 10000.0 };
+        // heights 0 3 2 0, one hole, bumpiness 3 + 1 + 2, wells 3 and 2 deep at the edges
+        let rows = board(&[
+            "....",
+            ".#..",
+            ".##.",
+            ".#..",
+        ]);
+        assert_eq!(evaluate(&rows, 0, &weights), 5.0 + 10.0 + 6.0 * 1000.0 + (6.0 + 3.0) * 10000.0);
+        assert_eq!(evaluate(&rows, 2, &weights), evaluate(&rows, 0, &weights) + 200.0);
+    }
+
+    #[test]
+    fn landings() {
+        let rows = board(&[".........."; 6]);
+        let collection = BrickCollection::new();
+        let o_brick = spawn(&rows, &collection.get_new_one(BrickType::O, 0)).unwrap();
+        // the O brick looks the same in every rotation
+        assert_eq!(get_landings(&rows, o_brick).len(), 9);
+        let i_brick = spawn(&rows, &collection.get_new_one(BrickType::I, 0)).unwrap();
+        let landings = get_landings(&rows, i_brick);
+        // lying in 7 columns and standing in 10
+        assert_eq!(landings.len(), 17);
+        assert!(landings.iter().all(|landing| landing.inputs.last() == Some(&GameInput::Drop)));
+        assert!(landings.iter().all(|landing| landing.lines == 0));
+    }
+
+    #[test]
+    fn fill_the_gap() {
+        // the I brick needs four empty rows to stand up
+        let rows = board(&[
+            "..........",
+            "..........",
+            "..........",
+            "..........",
+            "#########.",
+            "#########.",
+            "#########.",
+            "#########.",
+        ]);
+        let mut core = GameCore::new(rows.get_size());
+        core.manager.restore(&PanelSnapshot { blocks: rows, current_brick: None });
+        core.next_brick = Some(core.brick_collection.get_new_one(BrickType::O, 0));
+        let i_brick = core.brick_collection.get_new_one(BrickType::I, 0);
+        core.manager.put_new_one(&i_brick);
+        core.can_hold = false;
+        for input in find_inputs(&core, &DEFAULT_WEIGHTS) {
+            core.apply_input(input);
+        }
+        assert!(matches!(core.fall(), NextResult::LineClear(4, _)));
+        assert!(core.manager.snapshot().blocks.to_rows().iter().all(|row| row == ".........."));
+    }
+
+    #[test]
+    fn play_a_game() {
+        let size = Size { width: 10, height: 20 };
+        let timing = MasterTiming { are: 30, line_clear_delay: 41, lock_delay: 30 };
+        let mut core = GameCore::new(size);
+        core.start(GameMode::Classic, size, timing, 7);
+        let mut bot = Bot::new(DEFAULT_WEIGHTS);
+        let mut lines = 0;
+        for _ in 0..50 {
+            // a brick at a time, without gravity getting in the way
+            loop {
+                match core.fall() {
+                    NextResult::NewBrickPutIn(_) => break,
+                    NextResult::LineClear(count, _) => {
+                        lines += count;
+                        break;
+                    }
+                    NextResult::GameOver => panic!("the bot topped out after {} lines", lines),
+                    _ => {}
+                }
+            }
+            bot.on_new_brick(&core);
+            while let Some(input) = bot.next_input() {
+                core.apply_input(input);
+            }
+        }
+        assert!(lines >= 15, "only {} lines were cleared", lines);
+    }
+}
@@ -0,0 +1,221 @@
+//! The board, the bricks and the state of each mode.
+
+use crate::tetris::{Point, Size};
+use std::slice::Iter;
+use crate::data::bit_board::BitBoard;
+use crate::data::bricks::{Brick, BrickType};
+
+pub mod bit_board;
+pub mod bricks;
+pub mod game_panel;
+pub mod game_state;
+pub mod master;
+pub mod practice;
+pub mod puzzle;
+pub mod replay;
+pub mod save;
+pub mod versus;
+
+
+/// The cells of a board, `true` for filled ones.
+#[derive(Clone)]
+pub struct BlocksData {
+    /// Rows from the top, each with its cells from the left.
+    pub blocks: Vec<Vec<bool>>,
+    /// The tick each cell was locked on, `None` for empty cells and the falling brick.
+    pub locked_at: Vec<Vec<Option<u32>>>,
+    /// The brick each cell belongs to, `None` for empty cells and cells filled without a brick.
+    pub kinds: Vec<Vec<Option<BrickType>>>,
+    /// The ticks played on the board, cells are locked on this tick.
+    pub tick: u32,
+    block_size: Size,
+}
+
+fn create_empty_line(width: u16) -> Vec<bool> {
+    vec![false; width as usize]
+}
+
+fn create_empty_lock_line(width: u16) -> Vec<Option<u32>> {
+    vec![None; width as usize]
+}
+
+fn create_empty_kind_line(width: u16) -> Vec<Option<BrickType>> {
+    vec![None; width as usize]
+}
+
+/// The lines removed by [`BlocksData::try_clean_lines`].
+pub struct TryCleanLinesResult {
+    /// Indexes of the removed rows, from the bottom.
+    pub clear_lines: Vec<u16>,
+}
+
+impl BlocksData {
+    /// An empty board.
+    pub fn new(block_size: Size) -> BlocksData {
+        let mut blocks = Vec::new();
+        let mut locked_at = Vec::new();
+        let mut kinds = Vec::new();
+        for _line in 0..block_size.height {
+            blocks.push(create_empty_line(block_size.width));
+            locked_at.push(create_empty_lock_line(block_size.width));
+            kinds.push(create_empty_kind_line(block_size.width));
+        }
+        BlocksData {
+            blocks,
+            locked_at,
+            kinds,
+            tick: 0,
+            block_size,
+        }
+    }
+
+    /// Whether all points are on the board and set to `flag`.
+    pub fn test_points(&self, mut points: Iter<Point>, flag: bool) -> bool {
+        points.all(|point| {
+            let block = self.blocks.get(point.y as usize).and_then(|line| line.get(point.x as usize));
+            block == Some(&flag)
+        })
+    }
+
+    /// Fills or clears the points, they count as not locked and not belonging to a brick.
+    pub fn set_points(&mut self, points: Iter<Point>, flag: bool) {
+        for point in points {
+            self.blocks[point.y as usize][point.x as usize] = flag;
+            self.locked_at[point.y as usize][point.x as usize] = None;
+            self.kinds[point.y as usize][point.x as usize] = None;
+        }
+    }
+
+    /// Records that the points were locked on the current tick.
+    pub fn lock_points(&mut self, points: Iter<Point>) {
+        for point in points {
+            self.locked_at[point.y as usize][point.x as usize] = Some(self.tick);
+        }
+    }
+
+    /// Fills or clears the blocks of a brick, filled ones remember its type.
+    pub fn set_brick(&mut self, brick: &Brick, flag: bool) {
+        self.set_points(brick.points.iter(), flag);
+        if flag {
+            let kind = brick.get_type();
+            for point in brick.points.iter() {
+                self.kinds[point.y as usize][point.x as usize] = kind;
+            }
+        }
+    }
+
+    /// Fills or clears a rectangle starting at `point`.
+    pub fn set_region(&mut self, point: Point, size: Size, flag: bool) {
+        let vec = &mut self.blocks;
+        for y in point.y..point.y + size.height {
+            let line = &mut vec[y as usize];
+            let lock_line = &mut self.locked_at[y as usize];
+            let kind_line = &mut self.kinds[y as usize];
+            for x in point.x..point.x + size.width {
+                line[x as usize] = flag;
+                lock_line[x as usize] = None;
+                kind_line[x as usize] = None;
+            }
+        }
+    }
+
+    /// Loads rows of `#` (filled) and `.` (empty), aligned to the bottom of the board.
+    pub fn load_rows(&mut self, rows: &[String]) -> Result<(), String> {
+        let height = self.block_size.height as usize;
+        let width = self.block_size.width as usize;
+        if rows.len() > height {
+            return Err(format!("the board has {} rows, at most {} are allowed", rows.len(), height));
+        }
+        self.set_region(Point::new(0, 0), self.block_size, false);
+        let top = height - rows.len();
+        for (index, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(format!("row {} is not {} cells wide", index + 1, width));
+            }
+            for (x, c) in row.chars().enumerate() {
+                self.blocks[top + index][x] = match c {
+                    '#' => true,
+                    '.' => false,
+                    _ => return Err(format!("unknown cell '{}' in row {}", c, index + 1)),
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// A compact copy of the cells.
+    pub fn to_bit_board(&self) -> BitBoard {
+        let mut board = BitBoard::new(self.block_size);
+        for (y, line) in self.blocks.iter().enumerate() {
+            for (x, block) in line.iter().enumerate() {
+                if *block {
+                    board.set(Point::new(x as u16, y as u16), true);
+                }
+            }
+        }
+        board
+    }
+
+    /// A board with the cells of a bit board, lock times are not kept in a bit board.
+    pub fn from_bit_board(board: &BitBoard) -> BlocksData {
+        let mut data = BlocksData::new(board.get_size());
+        for (y, line) in data.blocks.iter_mut().enumerate() {
+            for (x, block) in line.iter_mut().enumerate() {
+                *block = board.get(Point::new(x as u16, y as u16));
+            }
+        }
+        data
+    }
+
+    /// Width and height in cells.
+    pub fn get_size(&self) -> Size {
+        self.block_size
+    }
+
+    /// Whether no cell is filled.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|line| line.iter().all(|x| { !*x }))
+    }
+
+    /// Moves every row up by one and fills the new bottom row except for the hole, which is how
+    /// garbage comes in. Returns whether the top row had blocks, they are pushed off the board.
+    pub fn push_garbage_line(&mut self, hole: u16) -> bool {
+        let width = self.block_size.width;
+        let overflow = self.blocks[0].iter().any(|x| *x);
+        self.blocks.remove(0);
+        self.locked_at.remove(0);
+        self.kinds.remove(0);
+        self.blocks.push((0..width).map(|x| x != hole).collect());
+        self.locked_at.push(create_empty_lock_line(width));
+        self.kinds.push(create_empty_kind_line(width));
+        overflow
+    }
+
+    /// Removes the filled rows and moves the rows above down, `None` if no row is filled.
+    pub fn try_clean_lines(&mut self) -> Option<TryCleanLinesResult> {
+        let mut result = Vec::new();
+        for y in 0..self.blocks.len() {
+            let line = &self.blocks[y];
+            if line.iter().all(|x| { *x }) {
+                result.push(y);
+            }
+        }
+        if result.is_empty() {
+            return None;
+        }
+        result.reverse();
+        for index in result.iter() {
+            self.blocks.remove(*index);
+            self.locked_at.remove(*index);
+            self.kinds.remove(*index);
+        }
+        for _index in result.iter() {
+            self.blocks.insert(0, create_empty_line(self.block_size.width));
+            self.locked_at.insert(0, create_empty_lock_line(self.block_size.width));
+            self.kinds.insert(0, create_empty_kind_line(self.block_size.width));
+        }
+        Some(TryCleanLinesResult {
+            clear_lines: result.iter().map(|i| { *i as u16 }).collect()
+        })
+    }
+}
\ No newline at end of file
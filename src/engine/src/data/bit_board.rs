@@ -0,0 +1,107 @@
+//! Compact copies of the board.
+
+use crate::tetris::{Point, Size};
+
+/// The widest board a bit board can hold.
+pub const MAX_BIT_BOARD_WIDTH: u16 = 32;
+
+/// A compact copy of the blocks, one bit per block.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct BitBoard {
+    size: Size,
+    rows: Vec<u32>,
+}
+
+impl BitBoard {
+    /// An empty board, panics if it is wider than [`MAX_BIT_BOARD_WIDTH`].
+    pub fn new(size: Size) -> BitBoard {
+        assert!(size.width <= MAX_BIT_BOARD_WIDTH);
+        BitBoard {
+            size,
+            rows: vec![0; size.height as usize],
+        }
+    }
+
+    /// Width and height in blocks.
+    pub fn get_size(&self) -> Size {
+        self.size
+    }
+
+    /// Whether the block is filled.
+    pub fn get(&self, point: Point) -> bool {
+        self.rows[point.y as usize] & (1 << point.x) != 0
+    }
+
+    /// Fills or clears the block.
+    pub fn set(&mut self, point: Point, flag: bool) {
+        let row = &mut self.rows[point.y as usize];
+        if flag {
+            *row |= 1 << point.x;
+        } else {
+            *row &= !(1 << point.x);
+        }
+    }
+
+    /// Rows of `#` (filled) and `.` (empty) from the top.
+    pub fn to_rows(&self) -> Vec<String> {
+        (0..self.size.height).map(|y| {
+            (0..self.size.width).map(|x| if self.get(Point::new(x, y)) { '#' } else { '.' }).collect()
+        }).collect()
+    }
+
+    /// Reads rows written by [`BitBoard::to_rows`], all rows must be equally wide.
+    pub fn from_rows(rows: &[String]) -> Result<BitBoard, String> {
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        if width > MAX_BIT_BOARD_WIDTH as usize {
+            return Err(format!("the board is {} cells wide, at most {} are allowed", width, MAX_BIT_BOARD_WIDTH));
+        }
+        let mut board = BitBoard::new(Size { width: width as u16, height: rows.len() as u16 });
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(format!("row {} is not {} cells wide", y + 1, width));
+            }
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '#' => board.set(Point::new(x as u16, y as u16), true),
+                    '.' => {}
+                    _ => return Err(format!("unknown cell '{}' in row {}", c, y + 1)),
+                }
+            }
+        }
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::bit_board::*;
+
+    #[test]
+    fn set_and_get() {
+        let mut board = BitBoard::new(Size { width: 10, height: 20 });
+        board.set(Point::new(9, 19), true);
+        board.set(Point::new(0, 0), true);
+        assert!(board.get(Point::new(9, 19)));
+        assert!(board.get(Point::new(0, 0)));
+        assert!(!board.get(Point::new(8, 19)));
+
+        board.set(Point::new(9, 19), false);
+        assert!(!board.get(Point::new(9, 19)));
+        assert_eq!(board, {
+            let mut expected = BitBoard::new(Size { width: 10, height: 20 });
+            expected.set(Point::new(0, 0), true);
+            expected
+        });
+    }
+
+    #[test]
+    fn to_rows_and_from_rows() {
+        let mut board = BitBoard::new(Size { width: 3, height: 2 });
+        board.set(Point::new(0, 1), true);
+        board.set(Point::new(2, 1), true);
+        let rows = board.to_rows();
+        assert_eq!(rows, vec![String::from("..."), String::from("#.#")]);
+        assert_eq!(BitBoard::from_rows(&rows), Ok(board));
+        assert!(BitBoard::from_rows(&[String::from("..."), String::from("#.")]).is_err());
+    }
+}
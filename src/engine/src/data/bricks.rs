@@ -0,0 +1,352 @@
+//! The seven bricks and the random brick generator.
+
+use std::collections::HashMap;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::tetris::Point;
+
+const BRICK_TYPE_COUNT: usize = 7;
+const BLOCK_COUNT_OF_BRICK: usize = 4;
+
+/// The blocks of a brick as `(x, y)` offsets.
+pub type TupleBrickPoints = [(u16, u16); BLOCK_COUNT_OF_BRICK];
+
+/// The seven kinds of bricks.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum BrickType {
+    /// The square.
+    O = 0x0,
+    /// The Z shape.
+    Z = 0x1,
+    /// The T shape.
+    T = 0x2,
+    /// The S shape.
+    S = 0x3,
+    /// The L shape.
+    L = 0x4,
+    /// The mirrored L, also known as J.
+    RL = 0x5,
+    /// The line of four.
+    I = 0x6,
+}
+
+/// The blocks of an O brick before rotating.
+pub static O_BRICK_POINTS: TupleBrickPoints = [(0, 0), (0, 1), (1, 0), (1, 1)];
+/// The blocks of a Z brick before rotating.
+pub static Z_BRICK_POINTS: TupleBrickPoints = [(0, 0), (1, 0), (1, 1), (2, 1)];
+/// The blocks of a T brick before rotating.
+pub static T_BRICK_POINTS: TupleBrickPoints = [(1, 0), (0, 1), (1, 1), (2, 1)];
+/// The blocks of an S brick before rotating.
+pub static S_BRICK_POINTS: TupleBrickPoints = [(1, 0), (2, 0), (0, 1), (1, 1)];
+/// The blocks of an L brick before rotating.
+pub static L_BRICK_POINTS: TupleBrickPoints = [(0, 0), (0, 1), (1, 1), (2, 1)];
+/// The blocks of a J brick before rotating.
+pub static RL_BRICK_POINTS: TupleBrickPoints = [(2, 0), (0, 1), (1, 1), (2, 1)];
+/// The blocks of an I brick before rotating.
+pub static I_BRICK_POINTS: TupleBrickPoints = [(0, 0), (1, 0), (2, 0), (3, 0)];
+
+impl BrickType {
+    fn from_usize(num: usize) -> BrickType {
+        match num {
+            0 => BrickType::O,
+            1 => BrickType::Z,
+            2 => BrickType::T,
+            3 => BrickType::S,
+            4 => BrickType::L,
+            5 => BrickType::RL,
+            6 => BrickType::I,
+            _ => BrickType::O
+        }
+    }
+
+    fn get_points(self) -> TupleBrickPoints {
+        match self {
+            BrickType::O => O_BRICK_POINTS,
+            BrickType::Z => Z_BRICK_POINTS,
+            BrickType::T => T_BRICK_POINTS,
+            BrickType::S => S_BRICK_POINTS,
+            BrickType::L => L_BRICK_POINTS,
+            BrickType::RL => RL_BRICK_POINTS,
+            BrickType::I => I_BRICK_POINTS,
+        }
+    }
+
+    /// The letter of the brick, `J` for [`BrickType::RL`].
+    pub fn to_char(self) -> char {
+        match self {
+            BrickType::O => 'O',
+            BrickType::Z => 'Z',
+            BrickType::T => 'T',
+            BrickType::S => 'S',
+            BrickType::L => 'L',
+            BrickType::RL => 'J',
+            BrickType::I => 'I',
+        }
+    }
+
+    /// The brick for a letter in any case, `None` for other characters.
+    pub fn from_char(c: char) -> Option<BrickType> {
+        match c.to_ascii_uppercase() {
+            'O' => Some(BrickType::O),
+            'Z' => Some(BrickType::Z),
+            'T' => Some(BrickType::T),
+            'S' => Some(BrickType::S),
+            'L' => Some(BrickType::L),
+            'J' => Some(BrickType::RL),
+            'I' => Some(BrickType::I),
+            _ => None
+        }
+    }
+}
+
+/// Every kind of brick, and the random generator handing them out.
+pub struct BrickCollection {
+    all_bricks: HashMap<BrickType, Brick>,
+    /// Random bricks follow from the seed, so a game can be played again.
+    rng: StdRng,
+    /// Random bricks handed out since the seed was set.
+    draws: u32,
+}
+
+impl Default for BrickCollection {
+    fn default() -> BrickCollection {
+        BrickCollection::new()
+    }
+}
+
+impl BrickCollection {
+    /// A collection with a random seed.
+    pub fn new() -> BrickCollection {
+        let all_bricks = create_all_brick();
+        BrickCollection {
+            all_bricks,
+            rng: StdRng::from_entropy(),
+            draws: 0,
+        }
+    }
+
+    /// Starts the random bricks over from the seed.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.draws = 0;
+    }
+
+    /// How many random bricks were handed out since the seed was set.
+    pub fn get_draws(&self) -> u32 {
+        self.draws
+    }
+
+    /// Brings the random state to where it was after the given draws.
+    pub fn skip(&mut self, draws: u32) {
+        for _i in 0..draws {
+            self.get_rand_one();
+        }
+    }
+
+    /// A random brick in a random rotation.
+    pub fn get_rand_one(&mut self) -> Brick {
+        let index = BrickType::from_usize(self.rng.gen_range(0..BRICK_TYPE_COUNT));
+        let rotate_times = self.rng.gen_range(0..4);
+        self.draws += 1;
+        self.get_new_one(index, rotate_times)
+    }
+
+    /// The type of a brick in any rotation.
+    pub fn find_type(&self, brick: &Brick) -> Option<BrickType> {
+        brick.get_type()
+    }
+
+    /// A brick of the type, rotated counterclockwise the given times.
+    pub fn get_new_one(&self, brick_type: BrickType, rotate_time: usize) -> Brick {
+        let mut new_block = self.all_bricks[&brick_type];
+        for _i in 0..rotate_time {
+            new_block.rotate();
+        }
+        new_block
+    }
+}
+
+/// The blocks of a brick as offsets from its position.
+pub type BrickPoints = [Point; BLOCK_COUNT_OF_BRICK];
+
+/// A brick in one rotation.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Brick {
+    /// Its blocks, relative to the top left corner.
+    pub points: BrickPoints,
+}
+
+impl Brick {
+    /// A brick with the given blocks.
+    pub fn new(points: TupleBrickPoints) -> Brick {
+        Brick {
+            points: create_points(points)
+        }
+    }
+    /// The blocks moved to `point`.
+    pub fn project_to_new_position(&self, point: Point) -> Brick {
+        let new_points = self.points.map(|t| {
+            Point {
+                y: point.y + t.y,
+                x: point.x + t.x,
+            }
+        });
+        Brick {
+            points: new_points
+        }
+    }
+    /// The type of the brick in any rotation and at any position.
+    pub fn get_type(&self) -> Option<BrickType> {
+        let left = self.points.iter().map(|p| p.x).min().unwrap();
+        let top = self.points.iter().map(|p| p.y).min().unwrap();
+        let mut points = self.points.map(|p| Point::new(p.x - left, p.y - top));
+        points.sort_by_key(|p| (p.y, p.x));
+        (0..BRICK_TYPE_COUNT).map(BrickType::from_usize).find(|brick_type| {
+            let mut brick = Brick::new(brick_type.get_points());
+            (0..4).any(|_| {
+                brick.rotate();
+                let mut type_points = brick.points;
+                type_points.sort_by_key(|p| (p.y, p.x));
+                type_points == points
+            })
+        })
+    }
+
+    /// The block touching the three others, only T bricks have one.
+    pub fn get_t_center(&self) -> Option<Point> {
+        self.points.iter().find(|center| {
+            self.points.iter().filter(|p| {
+                (p.x as i16 - center.x as i16).abs() + (p.y as i16 - center.y as i16).abs() == 1
+            }).count() == 3
+        }).copied()
+    }
+
+    /// Rotates the brick counterclockwise within its bounding box, rows go down the screen.
+    pub fn rotate(&mut self) {
+        let max_x = *self.points.map(|t| t.x).iter().max().unwrap();
+        for point in self.points.iter_mut() {
+            let old_x: u16 = point.x;
+            let old_y: u16 = point.y;
+            let new_x = old_y;
+            let new_y = if max_x == 0 { 0u16 } else { max_x - old_x };
+            point.x = new_x;
+            point.y = new_y;
+        }
+    }
+}
+
+
+fn create_points(points: TupleBrickPoints) -> BrickPoints {
+    points.map(|point| Point::new(point.0, point.1))
+}
+
+
+fn create_all_brick() -> HashMap<BrickType, Brick> {
+    (0..BRICK_TYPE_COUNT)
+        .map(BrickType::from_usize)
+        .map(|brick_type| (brick_type, Brick::new(brick_type.get_points())))
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::data::bricks::*;
+
+
+    #[test]
+    fn rotate_o() {
+        let source = O_BRICK_POINTS;
+        let expected = [(0, 1), (1, 1), (0, 0), (1, 0)];
+        test_rotate(source, expected);
+    }
+
+    #[test]
+    fn rotate_i() {
+        let source = I_BRICK_POINTS;
+        let expected = [(0, 3), (0, 2), (0, 1), (0, 0)];
+        test_rotate(source, expected);
+    }
+
+    #[test]
+    fn rotate_z() {
+        let source = Z_BRICK_POINTS;
+        let expected = [(0, 2), (0, 1), (1, 1), (1, 0)];
+        test_rotate(source, expected);
+    }
+
+    #[test]
+    fn rotate_z2() {
+        let source = [(0, 2), (0, 1), (1, 1), (1, 0)];
+        let expected = [(2, 1), (1, 1), (1, 0), (0, 0)];
+        test_rotate(source, expected);
+    }
+
+    fn test_rotate(source: TupleBrickPoints, expected: TupleBrickPoints) {
+        let source_points = create_points(source);
+        let expected_points = create_points(expected);
+        let mut brick = Brick {
+            points: source_points
+        };
+        brick.rotate();
+        println!("{:?}", source_points);
+        println!("{:?}", brick.points);
+        assert_eq!(brick.points, expected_points);
+    }
+
+    #[test]
+    fn find_type() {
+        let collection = BrickCollection::new();
+        for index in 0..BRICK_TYPE_COUNT {
+            let brick_type = BrickType::from_usize(index);
+            let brick = collection.get_new_one(brick_type, index % 4);
+            assert_eq!(collection.find_type(&brick), Some(brick_type));
+            let moved = brick.project_to_new_position(Point::new(3, 7));
+            assert_eq!(moved.get_type(), Some(brick_type));
+        }
+        let line = Brick::new([(0, 0), (1, 0), (2, 0), (4, 0)]);
+        assert_eq!(line.get_type(), None);
+    }
+
+    #[test]
+    fn same_seed_same_bricks() {
+        let mut collection = BrickCollection::new();
+        let mut other = BrickCollection::new();
+        collection.set_seed(42);
+        other.set_seed(42);
+        for _i in 0..20 {
+            assert_eq!(collection.get_rand_one(), other.get_rand_one());
+        }
+
+        // skipping the draws continues with the same bricks
+        other.set_seed(42);
+        other.skip(collection.get_draws());
+        assert_eq!(collection.get_rand_one(), other.get_rand_one());
+    }
+
+    #[test]
+    fn t_center() {
+        let mut brick = Brick::new(T_BRICK_POINTS);
+        assert_eq!(brick.get_t_center(), Some(Point::new(1, 1)));
+        brick.rotate();
+        brick.rotate();
+        assert_eq!(brick.get_t_center(), Some(Point::new(1, 0)));
+        assert_eq!(Brick::new(L_BRICK_POINTS).get_t_center(), None);
+        assert_eq!(Brick::new(I_BRICK_POINTS).get_t_center(), None);
+    }
+
+    #[test]
+    fn get_from_brick_collection() {
+        let collection = BrickCollection::new();
+        let mut brick = collection.get_new_one(BrickType::O, 0);
+        brick.rotate();
+        let expected_points = create_points([(0, 1), (1, 1), (0, 0), (1, 0)]);
+        assert_eq!(brick.points, expected_points);
+
+        let mut brick = collection.get_new_one(BrickType::O, 1);
+        brick.rotate();
+        let expected_points = create_points([(1, 1), (1, 0), (0, 1), (0, 0)]);
+        assert_eq!(brick.points, expected_points);
+    }
+}
+
@@ -0,0 +1,518 @@
+//! The board with the falling brick.
+
+use std::slice::Iter;
+use crate::data::{BlocksData, TryCleanLinesResult};
+use crate::data::bit_board::BitBoard;
+use crate::data::bricks::{Brick};
+use crate::tetris::{Movement, Point, Size};
+
+
+/// What moving the falling brick down did.
+#[derive(Eq, PartialEq, Debug)]
+pub enum MoveDownResult {
+    /// The brick moved down.
+    Success,
+    /// The brick was locked, a new one has to be put in.
+    NeedNewOne,
+}
+
+/// What putting in a new brick did.
+#[derive(Eq, PartialEq, Debug)]
+pub enum PutNewOneResult {
+    /// The brick is falling now.
+    Success,
+    /// There is no room for the brick.
+    GameOver,
+}
+
+/// The falling brick.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct LiveBrick {
+    /// The brick in its current rotation.
+    pub brick: Brick,
+    /// The top left corner of the brick on the board.
+    pub position: Point,
+}
+
+impl LiveBrick {
+    fn get_projected_brick(&self) -> Brick {
+        self.brick.clone().project_to_new_position(self.position)
+    }
+}
+
+/// The blocks without the falling brick, and the falling brick.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PanelSnapshot {
+    /// The locked blocks.
+    pub blocks: BitBoard,
+    /// The falling brick, if any.
+    pub current_brick: Option<LiveBrick>,
+}
+
+/// The board with the falling brick drawn into its blocks.
+pub struct GamePanel {
+    /// The locked blocks and the falling brick.
+    pub blocks: BlocksData,
+    /// The falling brick, if any.
+    pub current_brick: Option<LiveBrick>,
+    /// Whether the last locked brick was a T brick rotated into a slot with 3 corners filled.
+    pub last_lock_t_spin: bool,
+    last_move_rotated: bool,
+    block_size: Size,
+}
+
+impl GamePanel {
+    /// An empty board without a falling brick.
+    pub fn new(block_size: Size) -> GamePanel {
+        let data = BlocksData::new(block_size);
+        GamePanel {
+            blocks: data,
+            current_brick: None,
+            last_lock_t_spin: false,
+            last_move_rotated: false,
+            block_size,
+        }
+    }
+    /// Puts in a brick at the top center.
+    pub fn put_new_one(&mut self, new_brick: &Brick) -> PutNewOneResult {
+        let live_brick = LiveBrick {
+            brick: *new_brick,
+            position: Point::new(self.block_size.width / 2 - 2, 0),
+        };
+        if self.test_put_brick(&live_brick) {
+            self.current_brick = Some(live_brick);
+            self.last_move_rotated = false;
+            self.set_current_brick(true);
+            PutNewOneResult::Success
+        } else {
+            PutNewOneResult::GameOver
+        }
+    }
+
+    /// Moves the falling brick down by one row, or locks it if it has landed.
+    pub fn move_down(&mut self) -> MoveDownResult {
+        if self.move_current_brick(Movement {
+            y: 1,
+            x: 0,
+        }) {
+            MoveDownResult::Success
+        } else {
+            self.lock_current_brick();
+            self.current_brick = None;
+            MoveDownResult::NeedNewOne
+        }
+    }
+
+    /// Moves the falling brick, returns whether it fits there.
+    pub fn move_current_brick(&mut self, movement: Movement) -> bool {
+        let old_brick = match self.current_brick {
+            None => return false,
+            Some(live_brick) => live_brick,
+        };
+        let new_y = old_brick.position.y as i16 + movement.y;
+        let new_x = old_brick.position.x as i16 + movement.x;
+        if new_x < 0 || new_y < 0 {
+            return false;
+        }
+        let new_brick = LiveBrick {
+            brick: old_brick.brick,
+            position: Point {
+                y: (new_y) as u16,
+                x: (new_x) as u16,
+            },
+        };
+        self.set_current_brick(false);
+        let fits = self.test_put_brick(&new_brick);
+        if fits {
+            self.current_brick = Some(new_brick);
+            self.last_move_rotated = false;
+        }
+        self.set_current_brick(true);
+        fits
+    }
+
+    /// Whether the falling brick cannot move down any more.
+    pub fn is_current_brick_landed(&mut self) -> bool {
+        match self.current_brick {
+            None => false,
+            Some(live_brick) => {
+                let moved_brick = LiveBrick {
+                    position: Point::new(live_brick.position.x, live_brick.position.y + 1),
+                    ..live_brick
+                };
+                self.set_current_brick(false);
+                let fits = self.test_put_brick(&moved_brick);
+                self.set_current_brick(true);
+                !fits
+            }
+        }
+    }
+
+    /// Removes the filled rows, `None` if no row is filled.
+    pub fn try_clean_lines(&mut self) -> Option<TryCleanLinesResult> {
+        self.blocks.try_clean_lines()
+    }
+
+    /// Rotates the falling brick counterclockwise if it fits.
+    pub fn rotate_current_brick(&mut self) {
+        self.rotate_current_brick_times(1);
+    }
+
+    /// Rotates the falling brick counterclockwise the given times at once if it fits,
+    /// three times turns it clockwise.
+    pub fn rotate_current_brick_times(&mut self, times: usize) {
+        if let Some(current_brick) = self.current_brick {
+            self.set_current_brick(false);
+
+            // try to rotate
+            let mut brick = current_brick.brick;
+            for _ in 0..times {
+                brick.rotate();
+            }
+            let new_position = LiveBrick {
+                brick,
+                position: current_brick.position,
+            };
+            if self.test_put_brick(&new_position) {
+                self.current_brick = Some(new_position);
+                self.last_move_rotated = true;
+            }
+            self.set_current_brick(true);
+        }
+    }
+
+    /// Puts the falling brick at another place or rotation, returns whether it fits there.
+    pub fn move_current_brick_to(&mut self, live_brick: LiveBrick) -> bool {
+        self.set_current_brick(false);
+        let fits = self.test_put_brick(&live_brick);
+        if fits {
+            self.current_brick = Some(live_brick);
+            self.last_move_rotated = false;
+        }
+        self.set_current_brick(true);
+        fits
+    }
+
+    /// Moves the falling brick one column to the right if it fits.
+    pub fn move_current_brick_to_right(&mut self) {
+        self.move_current_brick(Movement {
+            x: 1,
+            y: 0,
+        });
+    }
+
+    /// Moves the falling brick one column to the left if it fits.
+    pub fn move_current_brick_to_left(&mut self) {
+        self.move_current_brick(Movement {
+            x: -1,
+            y: 0,
+        });
+    }
+
+    /// Drops the falling brick as far as it goes, it is locked by the next tick.
+    pub fn move_current_brick_to_bottom(&mut self) {
+        let movement = Movement {
+            x: 0,
+            y: 1,
+        };
+        while self.move_current_brick(movement) {}
+    }
+
+    fn test_put_brick(&self, current_brick: &LiveBrick) -> bool {
+        let new_position_brick = current_brick.get_projected_brick();
+        self.test_points(new_position_brick.points.iter(), false)
+    }
+
+    /// Fills or clears a rectangle of blocks.
+    pub fn set_region(&mut self, point: Point, size: Size, flag: bool) {
+        self.blocks.set_region(point, size, flag)
+    }
+
+    fn set_current_brick(&mut self, flag: bool) {
+        if let Some(live_brick) = self.current_brick {
+            let new_brick = live_brick.brick.project_to_new_position(live_brick.position);
+            self.blocks.set_brick(&new_brick, flag);
+        }
+    }
+
+    fn lock_current_brick(&mut self) {
+        if let Some(live_brick) = self.current_brick {
+            let new_brick = live_brick.get_projected_brick();
+            self.blocks.lock_points(new_brick.points.iter());
+            self.last_lock_t_spin = self.last_move_rotated && self.count_filled_corners(&new_brick) >= 3;
+        }
+    }
+
+    // count filled cells diagonal to the center of a T brick, walls and floor count as filled
+    fn count_filled_corners(&self, brick: &Brick) -> usize {
+        match brick.get_t_center() {
+            None => 0,
+            Some(center) => [(-1, -1), (1, -1), (-1, 1), (1, 1)].iter().filter(|(dx, dy)| {
+                let x = center.x as i16 + dx;
+                let y = center.y as i16 + dy;
+                x < 0 || y < 0 || !self.test_points([Point::new(x as u16, y as u16)].iter(), false)
+            }).count()
+        }
+    }
+
+    /// Puts a new brick in place of the falling one, the falling one stays if there is no room.
+    pub fn swap_current_brick(&mut self, new_brick: &Brick) -> PutNewOneResult {
+        self.set_current_brick(false);
+        let result = self.put_new_one(new_brick);
+        if result == PutNewOneResult::GameOver {
+            self.set_current_brick(true);
+        }
+        result
+    }
+
+    /// Takes the falling brick off the board.
+    pub fn remove_current_brick(&mut self) {
+        self.set_current_brick(false);
+        self.current_brick = None;
+    }
+
+    /// Replaces the blocks, without a falling brick, the ticks go on.
+    pub fn restore_blocks(&mut self, blocks: BlocksData) {
+        self.current_brick = None;
+        let tick = self.blocks.tick;
+        self.blocks = blocks;
+        self.blocks.tick = tick;
+    }
+
+    /// A copy of the board to [`GamePanel::restore`] later.
+    pub fn snapshot(&self) -> PanelSnapshot {
+        let mut blocks = self.blocks.to_bit_board();
+        if let Some(live_brick) = self.current_brick {
+            for point in live_brick.get_projected_brick().points.iter() {
+                blocks.set(*point, false);
+            }
+        }
+        PanelSnapshot {
+            blocks,
+            current_brick: self.current_brick,
+        }
+    }
+
+    /// Goes back to a snapshot, lock ticks are not kept, the stack counts as locked on the current tick.
+    pub fn restore(&mut self, snapshot: &PanelSnapshot) {
+        let tick = self.blocks.tick;
+        self.blocks = BlocksData::from_bit_board(&snapshot.blocks);
+        self.blocks.tick = tick;
+        let size = snapshot.blocks.get_size();
+        let stack: Vec<Point> = (0..size.height)
+            .flat_map(|y| (0..size.width).map(move |x| Point::new(x, y)))
+            .filter(|point| snapshot.blocks.get(*point))
+            .collect();
+        self.blocks.lock_points(stack.iter());
+        self.current_brick = snapshot.current_brick;
+        self.last_move_rotated = false;
+        self.set_current_brick(true);
+    }
+
+    /// Loads rows of `#` and `.` aligned to the bottom, without a falling brick.
+    pub fn load_board(&mut self, rows: &[String]) -> Result<(), String> {
+        self.current_brick = None;
+        self.blocks.load_rows(rows)
+    }
+
+    fn test_points(&self, points: Iter<Point>, flag: bool) -> bool {
+        self.blocks.test_points(points, flag)
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.set_region(Point::new(0, 0), self.block_size, false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::bricks::*;
+    use crate::data::game_panel::{GamePanel, LiveBrick, MoveDownResult, PutNewOneResult};
+    use crate::tetris::*;
+
+    static TEST_SIZE: Size = Size {
+        width: 10,
+        height: 20,
+    };
+
+
+    #[test]
+    fn put_new_one() {
+        let mut panel = GamePanel::new(TEST_SIZE);
+        let new_brick = Brick::new(Z_BRICK_POINTS);
+
+        // act
+        panel.put_new_one(&new_brick);
+        let brick = panel.current_brick.as_ref().unwrap();
+        assert_eq!(brick.brick, new_brick);
+        assert_eq!(brick.position, Point::new(TEST_SIZE.width / 2 - 2, 0));
+    }
+
+    #[test]
+    fn test_put_brick() {
+        let mut panel = GamePanel::new(TEST_SIZE);
+        let new_brick = Brick::new(Z_BRICK_POINTS);
+        let live_brick = LiveBrick {
+            brick: new_brick,
+            position: Point {
+                x: 0,
+                y: 0,
+            },
+        };
+        assert!(panel.test_put_brick(&live_brick));
+
+        // fill all blank with tags
+        panel.set_region(Point::new(0, 0), TEST_SIZE, true);
+
+        assert!(!panel.test_put_brick(&live_brick));
+    }
+
+    #[test]
+    fn move_down_success() {
+        let mut panel = GamePanel::new(TEST_SIZE);
+        let new_brick = Brick::new(Z_BRICK_POINTS);
+        let result = panel.put_new_one(&new_brick);
+        let old_position = panel.current_brick.unwrap().position;
+        assert_eq!(result, PutNewOneResult::Success);
+
+        // act
+        let down_result = panel.move_down();
+
+        assert_eq!(down_result, MoveDownResult::Success);
+        let current_brick = &panel.current_brick.unwrap();
+        assert_eq!(current_brick.brick, new_brick);
+        assert_eq!(current_brick.position.y, old_position.y + 1);
+        assert_eq!(current_brick.position.x, old_position.x);
+    }
+
+    #[test]
+    fn move_down_need_new_one() {
+        let mut panel = GamePanel::new(TEST_SIZE);
+        let new_brick = Brick::new(Z_BRICK_POINTS);
+        let result = panel.put_new_one(&new_brick);
+        assert_eq!(result, PutNewOneResult::Success);
+
+        let fill_size = Size {
+            height: TEST_SIZE.height - 2,
+            width: TEST_SIZE.width,
+        };
+        panel.set_region(Point::new(0, 2), fill_size, true);
+
+        // act
+        let down_result = panel.move_down();
+        assert_eq!(down_result, MoveDownResult::NeedNewOne);
+        assert!(panel.current_brick.is_none());
+    }
+
+    #[test]
+    fn move_down_lock_brick() {
+        let mut panel = GamePanel::new(TEST_SIZE);
+        let new_brick = Brick::new(Z_BRICK_POINTS);
+        panel.put_new_one(&new_brick);
+        let points = panel.current_brick.unwrap().get_projected_brick().points;
+        for point in points.iter() {
+            assert!(panel.blocks.locked_at[point.y as usize][point.x as usize].is_none());
+        }
+        let fill_size = Size {
+            height: TEST_SIZE.height - 2,
+            width: TEST_SIZE.width,
+        };
+        panel.set_region(Point::new(0, 2), fill_size, true);
+
+        panel.blocks.tick = 7;
+
+        // act
+        panel.move_down();
+        for point in points.iter() {
+            assert!(panel.blocks.blocks[point.y as usize][point.x as usize]);
+            assert_eq!(panel.blocks.locked_at[point.y as usize][point.x as usize], Some(7));
+        }
+    }
+
+    #[test]
+    fn brick_kinds() {
+        let mut panel = GamePanel::new(TEST_SIZE);
+        panel.set_region(Point::new(0, 19), Size { width: 10, height: 1 }, true);
+        panel.put_new_one(&Brick::new(Z_BRICK_POINTS));
+        panel.move_current_brick_to_bottom();
+        panel.move_down();
+        assert!(panel.current_brick.is_none());
+        assert_eq!(panel.blocks.kinds[0], vec![None; 10]);
+        assert_eq!(panel.blocks.kinds[19][0], None);
+        assert_eq!(panel.blocks.kinds[18].iter().filter(|kind| **kind == Some(BrickType::Z)).count(), 2);
+
+        // the kinds move down with the lines above a cleared line
+        panel.try_clean_lines().unwrap();
+        assert_eq!(panel.blocks.kinds[19].iter().filter(|kind| **kind == Some(BrickType::Z)).count(), 2);
+        assert_eq!(panel.blocks.kinds[18].iter().filter(|kind| **kind == Some(BrickType::Z)).count(), 2);
+    }
+
+    #[test]
+    fn current_brick_landed() {
+        let mut panel = GamePanel::new(TEST_SIZE);
+        assert!(!panel.is_current_brick_landed());
+        panel.put_new_one(&Brick::new(Z_BRICK_POINTS));
+        assert!(!panel.is_current_brick_landed());
+
+        panel.move_current_brick_to_bottom();
+        assert!(panel.is_current_brick_landed());
+        assert!(panel.current_brick.is_some());
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let mut panel = GamePanel::new(TEST_SIZE);
+        panel.set_region(Point::new(0, 19), Size { width: 9, height: 1 }, true);
+        panel.put_new_one(&Brick::new(T_BRICK_POINTS));
+        let snapshot = panel.snapshot();
+        assert!(!snapshot.blocks.get(Point::new(4, 0)));
+        assert!(snapshot.blocks.get(Point::new(0, 19)));
+
+        panel.move_current_brick_to_bottom();
+        panel.move_down();
+
+        // act
+        panel.restore(&snapshot);
+        assert_eq!(panel.current_brick, snapshot.current_brick);
+        assert!(panel.blocks.blocks[0][4]);
+        assert!(panel.blocks.blocks[19][0]);
+        assert!(!panel.blocks.blocks[18][4]);
+        assert_eq!(panel.snapshot(), snapshot);
+    }
+
+    #[test]
+    fn t_spin() {
+        let mut panel = GamePanel::new(TEST_SIZE);
+        let rows: Vec<String> = ["####...###", "###....###", "####.#####"].iter().map(|r| r.to_string()).collect();
+        panel.load_board(&rows).unwrap();
+        panel.put_new_one(&Brick::new(T_BRICK_POINTS));
+        panel.move_current_brick_to_right();
+        panel.move_current_brick_to_bottom();
+        panel.move_current_brick_to_left();
+        panel.rotate_current_brick();
+
+        // act
+        assert_eq!(panel.move_down(), MoveDownResult::NeedNewOne);
+        assert!(panel.last_lock_t_spin);
+        assert_eq!(panel.try_clean_lines().unwrap().clear_lines.len(), 1);
+    }
+
+    #[test]
+    fn try_clean_lines() {
+        let mut panel = GamePanel::new(TEST_SIZE);
+        // fill all blank with tags
+        panel.set_region(Point::new(0, 0), TEST_SIZE, true);
+
+        // act
+        let result = panel.try_clean_lines();
+        assert!(result.is_some());
+        let lines_result = result.unwrap();
+        assert_eq!(lines_result.clear_lines.len() as u16, TEST_SIZE.height);
+
+        assert_eq!(panel.blocks.blocks.len() as u16, TEST_SIZE.height);
+        // all clear
+        for vec in panel.blocks.blocks.iter() {
+            assert!(vec.iter().all(|x| { !*x }));
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! What another player sees of a game, sent over the network.
+
+use crate::data::BlocksData;
+use crate::data::bricks::{Brick, BrickType};
+use crate::game::GameCore;
+
+/// The board with the falling brick, the bricks and the score, written on one line. The cells
+/// are `.` when empty, the letter of their brick or `#` for garbage, and the rows are separated
+/// by `/`, `-` stands for a missing brick:
+///
+/// ```text
+/// 300 2 1 T - ........../....T...../...TTT..../#########.
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct GameState {
+    /// The score shown.
+    pub score: u32,
+    /// The cleared lines shown.
+    pub lines: u32,
+    /// Garbage rows waiting to rise in a versus game.
+    pub pending_garbage: u16,
+    /// The brick after the falling one.
+    pub next_brick: Option<BrickType>,
+    /// The held brick.
+    pub hold: Option<BrickType>,
+    /// The rows of the board from the top.
+    pub rows: Vec<String>,
+}
+
+impl GameState {
+    /// The state of the game with the score the player sees.
+    pub fn new(core: &GameCore, score: u32, lines: u32) -> GameState {
+        let blocks = &core.manager.blocks;
+        let rows = blocks.blocks.iter().zip(blocks.kinds.iter())
+            .map(|(line, kinds)| line.iter().zip(kinds.iter())
+                .map(|(filled, kind)| match (filled, kind) {
+                    (false, _) => '.',
+                    (true, Some(kind)) => kind.to_char(),
+                    (true, None) => '#',
+                })
+                .collect())
+            .collect();
+        GameState {
+            score,
+            lines,
+            pending_garbage: core.versus.as_ref().map_or(0, |versus| versus.get_pending_rows()),
+            next_brick: core.next_brick.as_ref().and_then(Brick::get_type),
+            hold: core.hold.as_ref().and_then(Brick::get_type),
+            rows,
+        }
+    }
+
+    /// Writes the state on one line.
+    pub fn to_text(&self) -> String {
+        let brick_to_text = |brick: Option<BrickType>| brick.map_or('-', BrickType::to_char);
+        format!("{} {} {} {} {} {}", self.score, self.lines, self.pending_garbage, brick_to_text(self.next_brick),
+                brick_to_text(self.hold), self.rows.join("/"))
+    }
+
+    /// Reads the line written by [`GameState::to_text`].
+    pub fn parse(text: &str) -> Result<GameState, String> {
+        let parts: Vec<&str> = text.split_whitespace().collect();
+        if parts.len() != 6 {
+            return Err(format!("expected 6 fields, found {}", parts.len()));
+        }
+        let parse_brick = |value: &str| match value {
+            "-" => Ok(None),
+            _ => {
+                let mut chars = value.chars();
+                match (chars.next().and_then(BrickType::from_char), chars.next()) {
+                    (Some(brick_type), None) => Ok(Some(brick_type)),
+                    _ => Err(format!("unknown brick '{}'", value)),
+                }
+            }
+        };
+        Ok(GameState {
+            score: parse_number(parts[0])?,
+            lines: parse_number(parts[1])?,
+            pending_garbage: parse_number(parts[2])?,
+            next_brick: parse_brick(parts[3])?,
+            hold: parse_brick(parts[4])?,
+            rows: parts[5].split('/').map(String::from).collect(),
+        })
+    }
+
+    /// Shows the board of the state, fails if it does not fit.
+    pub fn apply_to(&self, blocks: &mut BlocksData) -> Result<(), String> {
+        let size = blocks.get_size();
+        if self.rows.len() != size.height as usize {
+            return Err(format!("the board has {} rows, {} are expected", self.rows.len(), size.height));
+        }
+        for (y, row) in self.rows.iter().enumerate() {
+            if row.chars().count() != size.width as usize {
+                return Err(format!("row {} is not {} cells wide", y + 1, size.width));
+            }
+            for (x, c) in row.chars().enumerate() {
+                let (filled, kind) = match c {
+                    '.' => (false, None),
+                    '#' => (true, None),
+                    _ => (true, Some(BrickType::from_char(c).ok_or(format!("unknown cell '{}' in row {}", c, y + 1))?)),
+                };
+                blocks.blocks[y][x] = filled;
+                blocks.kinds[y][x] = kind;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("invalid number '{}'", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::BlocksData;
+    use crate::data::bricks::BrickType;
+    use crate::data::game_state::*;
+    use crate::game::GameCore;
+    use crate::tetris::{GameMode, MasterTiming, Point, Size};
+
+    #[test]
+    fn to_text_and_parse() {
+        let size = Size { width: 4, height: 3 };
+        let timing = MasterTiming { are: 30, line_clear_delay: 41, lock_delay: 30 };
+        let mut core = GameCore::new(size);
+        core.start(GameMode::Versus, size, timing, 42);
+        core.manager.blocks.set_region(Point::new(0, 2), Size { width: 3, height: 1 }, true);
+        core.versus.as_mut().unwrap().receive(2);
+        core.next_brick = None;
+        let mut state = GameState::new(&core, 300, 2);
+        assert_eq!(state.to_text(), "300 2 2 - - ..../..../###.");
+        state.next_brick = Some(BrickType::RL);
+        state.rows[0] = String::from(".TT.");
+        let text = state.to_text();
+        assert_eq!(text, "300 2 2 J - .TT./..../###.");
+        assert_eq!(GameState::parse(&text), Ok(state.clone()));
+
+        let mut blocks = BlocksData::new(size);
+        state.apply_to(&mut blocks).unwrap();
+        assert_eq!(blocks.blocks[0], vec![false, true, true, false]);
+        assert_eq!(blocks.kinds[0][1], Some(BrickType::T));
+        assert_eq!(blocks.kinds[2][0], None);
+        assert!(state.apply_to(&mut BlocksData::new(Size { width: 5, height: 3 })).is_err());
+    }
+
+    #[test]
+    fn parse_error() {
+        assert!(GameState::parse("300 2 0 - -").is_err());
+        assert!(GameState::parse("300 2 0 X - ....").is_err());
+        assert!(GameState::parse("300 x 0 - - ....").is_err());
+    }
+}
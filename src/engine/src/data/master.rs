@@ -0,0 +1,244 @@
+//! Gravity, grades and progress of master mode.
+
+/// Master mode runs at 60 frames per second.
+pub const FRAMES_PER_SECOND: u32 = 60;
+/// Gravity is counted in 1/256 cell per frame, this much moves a brick down by one cell.
+pub const GRAVITY_UNIT: u32 = 256;
+/// The game is finished at this level.
+pub const MAX_LEVEL: u16 = 999;
+/// Levels in each section.
+pub const SECTION_LEVELS: u16 = 100;
+
+// (level, gravity) pairs, the gravity applies from the level on
+static GRAVITY_TABLE: [(u16, u32); 30] = [
+    (0, 4), (30, 6), (35, 8), (40, 10), (50, 12), (60, 16), (70, 32), (80, 48), (90, 64),
+    (100, 80), (120, 96), (140, 112), (160, 128), (170, 144), (200, 4), (220, 32), (230, 64),
+    (233, 96), (236, 128), (239, 160), (243, 192), (247, 224), (251, 256), (300, 512),
+    (330, 768), (360, 1024), (400, 1280), (420, 1024), (450, 768), (500, 5120),
+];
+
+// (score, grade) pairs, the grade is reached with the score
+static GRADE_TABLE: [(u32, &str); 18] = [
+    (0, "9"), (400, "8"), (800, "7"), (1400, "6"), (2000, "5"), (3500, "4"),
+    (5500, "3"), (8000, "2"), (12000, "1"), (16000, "S1"), (22000, "S2"), (30000, "S3"),
+    (40000, "S4"), (52000, "S5"), (66000, "S6"), (82000, "S7"), (100000, "S8"), (120000, "S9"),
+];
+
+/// The gravity at the level, in [`GRAVITY_UNIT`]s per frame.
+pub fn get_gravity(level: u16) -> u32 {
+    GRAVITY_TABLE.iter()
+        .rev()
+        .find(|(from, _)| *from <= level)
+        .map(|(_, gravity)| *gravity)
+        .unwrap_or(GRAVITY_TABLE[0].1)
+}
+
+/// The grade reached with the score, from 9 up to S9.
+pub fn get_grade(score: u32) -> &'static str {
+    GRADE_TABLE.iter()
+        .rev()
+        .find(|(from, _)| *from <= score)
+        .map(|(_, grade)| *grade)
+        .unwrap_or(GRADE_TABLE[0].1)
+}
+
+/// The frames as `mm:ss:cc`.
+pub fn format_frames(frames: u32) -> String {
+    let seconds = frames / FRAMES_PER_SECOND;
+    let centis = frames % FRAMES_PER_SECOND * 100 / FRAMES_PER_SECOND;
+    format!("{:02}:{:02}:{:02}", seconds / 60, seconds % 60, centis)
+}
+
+/// What is shown about a master game.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct MasterInfo {
+    /// The level from 0 to [`MAX_LEVEL`].
+    pub level: u16,
+    /// The score.
+    pub score: u32,
+    /// The cleared lines.
+    pub lines: u32,
+    /// The grade reached with the score.
+    pub grade: &'static str,
+    /// Frames since the current section began.
+    pub section_frames: u32,
+    /// Frames the last finished section took.
+    pub last_section_frames: Option<u32>,
+}
+
+/// Level, score and section times of a master game.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct MasterProgress {
+    /// The level from 0 to [`MAX_LEVEL`].
+    pub level: u16,
+    /// The score.
+    pub score: u32,
+    /// The cleared lines.
+    pub lines: u32,
+    /// Frames since the game began.
+    pub frames: u32,
+    combo: u32,
+    section_start_frame: u32,
+    section_times: Vec<u32>,
+}
+
+impl Default for MasterProgress {
+    fn default() -> MasterProgress {
+        MasterProgress::new()
+    }
+}
+
+impl MasterProgress {
+    /// A game at level 0.
+    pub fn new() -> MasterProgress {
+        MasterProgress {
+            level: 0,
+            score: 0,
+            lines: 0,
+            frames: 0,
+            combo: 1,
+            section_start_frame: 0,
+            section_times: Vec::new(),
+        }
+    }
+
+    /// Counts one frame.
+    pub fn tick(&mut self) {
+        self.frames += 1;
+    }
+
+    /// Goes up one level when a brick appears.
+    pub fn on_spawn(&mut self) {
+        // the level stops at the end of each section until lines are cleared
+        if self.level % SECTION_LEVELS != SECTION_LEVELS - 1 && self.level != MAX_LEVEL - 1 {
+            self.level += 1;
+        }
+    }
+
+    /// Scores the lines cleared by a locked brick, `bravo` if the board is empty afterwards.
+    /// Returns true if the game is finished.
+    pub fn on_lock(&mut self, lines: u16, bravo: bool) -> bool {
+        if lines == 0 {
+            self.combo = 1;
+            return false;
+        }
+        let lines = lines as u32;
+        self.combo += 2 * lines - 2;
+        let bravo = if bravo { 4 } else { 1 };
+        self.score += (self.level as u32 + lines).div_ceil(4) * lines * self.combo * bravo;
+        self.lines += lines;
+
+        let old_section = self.level / SECTION_LEVELS;
+        self.level = (self.level + lines as u16).min(MAX_LEVEL);
+        if self.level / SECTION_LEVELS != old_section {
+            self.section_times.push(self.frames - self.section_start_frame);
+            self.section_start_frame = self.frames;
+        }
+        self.level == MAX_LEVEL
+    }
+
+    /// Level, score, lines, frames, combo, section start and section times separated by spaces.
+    pub fn to_text(&self) -> String {
+        let mut values = vec![
+            self.level as u32, self.score, self.lines, self.frames, self.combo, self.section_start_frame,
+        ];
+        values.extend(self.section_times.iter());
+        values.iter().map(|value| value.to_string()).collect::<Vec<String>>().join(" ")
+    }
+
+    /// Reads the text written by [`MasterProgress::to_text`].
+    pub fn parse(text: &str) -> Result<MasterProgress, String> {
+        let values = text.split_whitespace()
+            .map(|value| value.parse::<u32>().map_err(|_| format!("invalid number '{}'", value)))
+            .collect::<Result<Vec<u32>, String>>()?;
+        if values.len() < 6 || values[0] > MAX_LEVEL as u32 {
+            return Err(format!("invalid master progress '{}'", text));
+        }
+        Ok(MasterProgress {
+            level: values[0] as u16,
+            score: values[1],
+            lines: values[2],
+            frames: values[3],
+            combo: values[4],
+            section_start_frame: values[5],
+            section_times: values[6..].to_vec(),
+        })
+    }
+
+    /// What is shown about the game.
+    pub fn get_info(&self) -> MasterInfo {
+        MasterInfo {
+            level: self.level,
+            score: self.score,
+            lines: self.lines,
+            grade: get_grade(self.score),
+            section_frames: self.frames - self.section_start_frame,
+            last_section_frames: self.section_times.last().copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::master::*;
+
+    #[test]
+    fn gravity() {
+        assert_eq!(get_gravity(0), 4);
+        assert_eq!(get_gravity(34), 6);
+        assert_eq!(get_gravity(200), 4);
+        assert_eq!(get_gravity(251), GRAVITY_UNIT);
+        assert_eq!(get_gravity(MAX_LEVEL), 20 * GRAVITY_UNIT);
+    }
+
+    #[test]
+    fn grade() {
+        assert_eq!(get_grade(0), "9");
+        assert_eq!(get_grade(399), "9");
+        assert_eq!(get_grade(12000), "1");
+        assert_eq!(get_grade(1000000), "S9");
+    }
+
+    #[test]
+    fn level_stops_at_section_end() {
+        let mut progress = MasterProgress::new();
+        for _i in 0..200 {
+            progress.on_spawn();
+        }
+        assert_eq!(progress.level, 99);
+
+        progress.tick();
+        assert!(!progress.on_lock(1, false));
+        assert_eq!(progress.level, 100);
+        assert_eq!(progress.score, 25);
+        let info = progress.get_info();
+        assert_eq!(info.last_section_frames, Some(1));
+        assert_eq!(info.section_frames, 0);
+    }
+
+    #[test]
+    fn finish_at_max_level() {
+        let mut progress = MasterProgress::new();
+        progress.level = MAX_LEVEL - 2;
+        assert!(progress.on_lock(4, false));
+        assert_eq!(progress.level, MAX_LEVEL);
+    }
+
+    #[test]
+    fn to_text_and_parse() {
+        let mut progress = MasterProgress::new();
+        progress.level = 99;
+        progress.tick();
+        progress.on_lock(2, false);
+        let text = progress.to_text();
+        assert_eq!(text, "101 156 2 1 3 1 1");
+        assert_eq!(MasterProgress::parse(&text), Ok(progress));
+        assert!(MasterProgress::parse("1 2 3").is_err());
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(format_frames(0), "00:00:00");
+        assert_eq!(format_frames(61 * FRAMES_PER_SECOND + 30), "01:01:50");
+    }
+}
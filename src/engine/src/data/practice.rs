@@ -0,0 +1,169 @@
+//! Setups and placement history of practice mode.
+
+use std::collections::VecDeque;
+use crate::data::BlocksData;
+use crate::data::bit_board::BitBoard;
+use crate::data::bricks::{Brick, BrickType};
+use crate::data::game_panel::PanelSnapshot;
+use crate::tetris::Size;
+
+// the oldest placements are dropped beyond this
+const MAX_HISTORY: usize = 1000;
+
+/// The game right after a brick was put in.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Placement {
+    /// The board with the new falling brick.
+    pub panel: PanelSnapshot,
+    /// The brick after it.
+    pub next_brick: Option<Brick>,
+    /// The chosen pieces after it.
+    pub queue: VecDeque<BrickType>,
+    /// The held brick.
+    pub hold: Option<Brick>,
+}
+
+/// The setup and the history of a practice game.
+pub struct PracticeState {
+    /// Chosen pieces, random ones follow when it runs out.
+    pub queue: VecDeque<BrickType>,
+    setup: BitBoard,
+    setup_queue: VecDeque<BrickType>,
+    // the last one is the placement of the falling brick
+    history: Vec<Placement>,
+    redo_history: Vec<Placement>,
+}
+
+impl PracticeState {
+    /// An empty setup without chosen pieces.
+    pub fn new(block_size: Size) -> PracticeState {
+        PracticeState {
+            queue: VecDeque::new(),
+            setup: BitBoard::new(block_size),
+            setup_queue: VecDeque::new(),
+            history: Vec::new(),
+            redo_history: Vec::new(),
+        }
+    }
+
+    /// Keeps the board and the queue to start over from, the history is cleared.
+    pub fn save_setup(&mut self, blocks: &BlocksData) {
+        self.setup = blocks.to_bit_board();
+        self.setup_queue = self.queue.clone();
+        self.clear_history();
+    }
+
+    /// The saved board, the queue is restored as well.
+    pub fn restore_setup(&mut self) -> BlocksData {
+        self.queue = self.setup_queue.clone();
+        self.clear_history();
+        BlocksData::from_bit_board(&self.setup)
+    }
+
+    /// Records a placement, what could be redone is dropped.
+    pub fn on_put_in(&mut self, placement: Placement) {
+        if self.history.len() == MAX_HISTORY {
+            self.history.remove(0);
+        }
+        self.history.push(placement);
+        self.redo_history.clear();
+    }
+
+    /// The placement before the falling brick, it becomes the current one again.
+    pub fn undo(&mut self) -> Option<Placement> {
+        if self.history.len() < 2 {
+            return None;
+        }
+        self.redo_history.push(self.history.pop().unwrap());
+        self.history.last().cloned()
+    }
+
+    /// The placement taken back by the last undo.
+    pub fn redo(&mut self) -> Option<Placement> {
+        let placement = self.redo_history.pop()?;
+        self.history.push(placement.clone());
+        Some(placement)
+    }
+
+    /// Forgets all placements.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.redo_history.clear();
+    }
+
+    /// The letters of the chosen pieces.
+    pub fn get_queue_text(&self) -> String {
+        self.queue.iter().map(|brick_type| brick_type.to_char()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use crate::data::BlocksData;
+    use crate::data::bit_board::BitBoard;
+    use crate::data::bricks::*;
+    use crate::data::game_panel::{LiveBrick, PanelSnapshot};
+    use crate::data::practice::*;
+    use crate::tetris::{Point, Size};
+
+    static TEST_SIZE: Size = Size {
+        width: 10,
+        height: 20,
+    };
+
+    fn create_placement(brick: Brick) -> Placement {
+        Placement {
+            panel: PanelSnapshot {
+                blocks: BitBoard::new(TEST_SIZE),
+                current_brick: Some(LiveBrick {
+                    brick,
+                    position: Point::new(3, 0),
+                }),
+            },
+            next_brick: None,
+            queue: VecDeque::new(),
+            hold: None,
+        }
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut state = PracticeState::new(TEST_SIZE);
+        let first = create_placement(Brick::new(O_BRICK_POINTS));
+        let second = create_placement(Brick::new(I_BRICK_POINTS));
+        let third = create_placement(Brick::new(T_BRICK_POINTS));
+        state.on_put_in(first.clone());
+        assert!(state.undo().is_none());
+        state.on_put_in(second.clone());
+        state.on_put_in(third.clone());
+
+        assert_eq!(state.undo(), Some(second.clone()));
+        assert_eq!(state.undo(), Some(first.clone()));
+        assert!(state.undo().is_none());
+        assert_eq!(state.redo(), Some(second.clone()));
+        assert_eq!(state.redo(), Some(third));
+        assert!(state.redo().is_none());
+
+        // a new placement drops what could be redone
+        state.undo();
+        state.on_put_in(first.clone());
+        assert!(state.redo().is_none());
+        assert_eq!(state.undo(), Some(second));
+    }
+
+    #[test]
+    fn restore_setup() {
+        let mut state = PracticeState::new(TEST_SIZE);
+        let mut blocks = BlocksData::new(TEST_SIZE);
+        blocks.set_region(Point::new(0, 19), Size { width: 9, height: 1 }, true);
+        state.queue.push_back(BrickType::I);
+        state.save_setup(&blocks);
+        assert_eq!(state.get_queue_text(), "I");
+
+        state.queue.clear();
+        let restored = state.restore_setup();
+        assert_eq!(restored.blocks, blocks.blocks);
+        assert_eq!(state.get_queue_text(), "I");
+    }
+}
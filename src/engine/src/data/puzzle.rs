@@ -0,0 +1,190 @@
+//! Puzzle files and the progress through a puzzle.
+
+use std::collections::VecDeque;
+use crate::data::bricks::BrickType;
+
+// puzzles shipped with the game
+static BUILT_IN_PUZZLES: [&str; 5] = [
+    include_str!("../../asset/puzzles/01_first_tetris.txt"),
+    include_str!("../../asset/puzzles/02_double_up.txt"),
+    include_str!("../../asset/puzzles/03_hook.txt"),
+    include_str!("../../asset/puzzles/04_t_spin_single.txt"),
+    include_str!("../../asset/puzzles/05_two_for_two.txt"),
+];
+
+/// What solves a puzzle.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PuzzleGoal {
+    /// Clear at least this many lines.
+    Lines(u16),
+    /// Perform a T-spin clearing exactly this many lines.
+    TSpin(u16),
+}
+
+impl PuzzleGoal {
+    /// A short text for the goal.
+    pub fn describe(&self) -> String {
+        match self {
+            PuzzleGoal::Lines(lines) => format!("Clear {} lines", lines),
+            PuzzleGoal::TSpin(lines) => format!("T-spin x{}", lines),
+        }
+    }
+}
+
+/// A puzzle file looks like this, the board is aligned to the bottom of the game panel:
+///
+/// ```text
+/// name: First Tetris
+/// goal: lines 4
+/// pieces: I
+/// board:
+/// #########.
+/// #########.
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Puzzle {
+    /// Shown in the list of puzzles.
+    pub name: String,
+    /// What solves the puzzle.
+    pub goal: PuzzleGoal,
+    /// The pieces in the order they are handed out.
+    pub pieces: Vec<BrickType>,
+    /// Rows of `#` and `.`, aligned to the bottom.
+    pub board: Vec<String>,
+}
+
+impl Puzzle {
+    /// Reads a puzzle file.
+    pub fn parse(content: &str) -> Result<Puzzle, String> {
+        let mut name = None;
+        let mut goal = None;
+        let mut pieces = None;
+        let mut board = Vec::new();
+        let mut in_board = false;
+        for line in content.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            if in_board {
+                board.push(line.to_string());
+                continue;
+            }
+            let (key, value) = match line.find(':') {
+                Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+                None => return Err(format!("expected 'key: value', found '{}'", line)),
+            };
+            match key {
+                "name" => name = Some(value.to_string()),
+                "goal" => goal = Some(parse_goal(value)?),
+                "pieces" => pieces = Some(parse_pieces(value)?),
+                "board" => in_board = true,
+                _ => return Err(format!("unknown key '{}'", key)),
+            }
+        }
+        let pieces = pieces.ok_or("missing pieces")?;
+        if pieces.is_empty() {
+            return Err(String::from("no pieces given"));
+        }
+        Ok(Puzzle {
+            name: name.ok_or("missing name")?,
+            goal: goal.ok_or("missing goal")?,
+            pieces,
+            board,
+        })
+    }
+
+    /// The puzzles shipped with the game.
+    pub fn get_built_in() -> Vec<Puzzle> {
+        BUILT_IN_PUZZLES.iter().map(|content| Puzzle::parse(content).unwrap()).collect()
+    }
+}
+
+fn parse_goal(value: &str) -> Result<PuzzleGoal, String> {
+    let mut parts = value.split_whitespace();
+    let kind = parts.next().unwrap_or("");
+    let lines = parts.next()
+        .and_then(|count| count.parse::<u16>().ok())
+        .ok_or(format!("invalid goal '{}'", value))?;
+    match kind {
+        "lines" => Ok(PuzzleGoal::Lines(lines)),
+        "tspin" => Ok(PuzzleGoal::TSpin(lines)),
+        _ => Err(format!("unknown goal '{}'", kind)),
+    }
+}
+
+fn parse_pieces(value: &str) -> Result<Vec<BrickType>, String> {
+    value.chars()
+        .filter(|c| !c.is_whitespace() && *c != ',')
+        .map(|c| BrickType::from_char(c).ok_or(format!("unknown piece '{}'", c)))
+        .collect()
+}
+
+/// Progress through a puzzle while it is played.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PuzzleState {
+    /// What solves the puzzle.
+    pub goal: PuzzleGoal,
+    /// Lines cleared so far.
+    pub lines: u16,
+    /// Pieces which are not handed out yet.
+    pub pieces: VecDeque<BrickType>,
+}
+
+impl PuzzleState {
+    /// A puzzle with all pieces left.
+    pub fn new(puzzle: &Puzzle) -> PuzzleState {
+        PuzzleState {
+            goal: puzzle.goal,
+            lines: 0,
+            pieces: puzzle.pieces.iter().copied().collect(),
+        }
+    }
+
+    /// Counts the lines cleared by a locked brick, returns true if the goal is reached.
+    pub fn on_lock(&mut self, lines: u16, t_spin: bool) -> bool {
+        self.lines += lines;
+        match self.goal {
+            PuzzleGoal::Lines(goal) => self.lines >= goal,
+            PuzzleGoal::TSpin(goal) => t_spin && lines == goal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::bricks::BrickType;
+    use crate::data::puzzle::*;
+
+    #[test]
+    fn parse() {
+        let puzzle = Puzzle::parse("name: Test\ngoal: tspin 2\npieces: T, I\nboard:\n##..\n#.##\n").unwrap();
+        assert_eq!(puzzle.name, "Test");
+        assert_eq!(puzzle.goal, PuzzleGoal::TSpin(2));
+        assert_eq!(puzzle.pieces, vec![BrickType::T, BrickType::I]);
+        assert_eq!(puzzle.board, vec!["##..", "#.##"]);
+    }
+
+    #[test]
+    fn parse_error() {
+        assert!(Puzzle::parse("name: Test\ngoal: lines 1\nboard:\n").is_err());
+        assert!(Puzzle::parse("name: Test\ngoal: lines\npieces: I\n").is_err());
+        assert!(Puzzle::parse("name: Test\ngoal: lines 1\npieces: X\n").is_err());
+    }
+
+    #[test]
+    fn built_in() {
+        assert_eq!(Puzzle::get_built_in().len(), BUILT_IN_PUZZLES.len());
+    }
+
+    #[test]
+    fn goal() {
+        let mut puzzle = Puzzle::parse("name: Test\ngoal: lines 3\npieces: T\n").unwrap();
+        let mut state = PuzzleState::new(&puzzle);
+        assert_eq!(state.pieces.len(), 1);
+        assert!(!state.on_lock(2, false));
+        assert!(state.on_lock(1, false));
+
+        puzzle.goal = PuzzleGoal::TSpin(1);
+        let mut state = PuzzleState::new(&puzzle);
+        assert!(!state.on_lock(1, false));
+        assert!(!state.on_lock(2, true));
+        assert!(state.on_lock(1, true));
+    }
+}
@@ -0,0 +1,200 @@
+//! Games recorded as a seed and the inputs.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::tetris::GameMode;
+
+/// Replays are saved to this folder.
+pub static REPLAY_FOLDER: &str = "replays";
+
+/// Everything the player can do to the falling brick.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum GameInput {
+    /// Move one column to the left.
+    Left,
+    /// Move one column to the right.
+    Right,
+    /// Rotate clockwise.
+    Rotate,
+    /// Drop to the bottom, the brick locks on the next tick.
+    Drop,
+    /// Move one row down.
+    SoftDrop,
+    /// Rotate counterclockwise.
+    RotateCcw,
+    /// Rotate twice.
+    Rotate180,
+    /// Swap the falling brick with the held one, once per brick.
+    Hold,
+}
+
+impl GameInput {
+    /// Whether the input turns the brick, in any direction.
+    pub fn is_rotation(&self) -> bool {
+        matches!(self, GameInput::Rotate | GameInput::RotateCcw | GameInput::Rotate180)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            GameInput::Left => "left",
+            GameInput::Right => "right",
+            GameInput::Rotate => "rotate",
+            GameInput::Drop => "drop",
+            GameInput::SoftDrop => "soft_drop",
+            GameInput::RotateCcw => "rotate_ccw",
+            GameInput::Rotate180 => "rotate_180",
+            GameInput::Hold => "hold",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<GameInput> {
+        match name {
+            "left" => Some(GameInput::Left),
+            "right" => Some(GameInput::Right),
+            "rotate" => Some(GameInput::Rotate),
+            "drop" => Some(GameInput::Drop),
+            "soft_drop" => Some(GameInput::SoftDrop),
+            "rotate_ccw" => Some(GameInput::RotateCcw),
+            "rotate_180" => Some(GameInput::Rotate180),
+            "hold" => Some(GameInput::Hold),
+            _ => None,
+        }
+    }
+}
+
+/// A game is played again from its seed and inputs, each input is applied
+/// after the given number of ticks:
+///
+/// ```text
+/// mode: Classic
+/// seed: 1234
+/// ticks: 360
+/// inputs:
+/// 3 left
+/// 5 drop
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Replay {
+    /// The mode the game was played in.
+    pub mode: GameMode,
+    /// The seed of the random bricks.
+    pub seed: u64,
+    /// How long the game lasted.
+    pub ticks: u32,
+    /// The inputs with the ticks before them.
+    pub inputs: Vec<(u32, GameInput)>,
+}
+
+impl Replay {
+    /// An empty recording.
+    pub fn new(mode: GameMode, seed: u64) -> Replay {
+        Replay {
+            mode,
+            seed,
+            ticks: 0,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Records an input, it happens before the next tick.
+    pub fn push(&mut self, input: GameInput) {
+        self.inputs.push((self.ticks, input));
+    }
+
+    /// The replay in the format shown above.
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("mode: {}", self.mode.name()),
+            format!("seed: {}", self.seed),
+            format!("ticks: {}", self.ticks),
+            String::from("inputs:"),
+        ];
+        lines.extend(self.inputs.iter().map(|(tick, input)| format!("{} {}", tick, input.name())));
+        lines.join("\n")
+    }
+
+    /// Reads the text written by [`Replay::to_text`].
+    pub fn parse(content: &str) -> Result<Replay, String> {
+        let mut mode = None;
+        let mut seed = None;
+        let mut ticks = None;
+        let mut inputs = Vec::new();
+        let mut in_inputs = false;
+        for line in content.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            if in_inputs {
+                inputs.push(parse_input(line)?);
+                continue;
+            }
+            let (key, value) = match line.find(':') {
+                Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+                None => return Err(format!("expected 'key: value', found '{}'", line)),
+            };
+            match key {
+                "mode" => mode = Some(GameMode::from_name(value).ok_or(format!("unknown mode '{}'", value))?),
+                "seed" => seed = Some(value.parse::<u64>().map_err(|_| format!("invalid seed '{}'", value))?),
+                "ticks" => ticks = Some(value.parse::<u32>().map_err(|_| format!("invalid ticks '{}'", value))?),
+                "inputs" => in_inputs = true,
+                _ => return Err(format!("unknown key '{}'", key)),
+            }
+        }
+        Ok(Replay {
+            mode: mode.ok_or("missing mode")?,
+            seed: seed.ok_or("missing seed")?,
+            ticks: ticks.ok_or("missing ticks")?,
+            inputs,
+        })
+    }
+
+    /// Writes the replay to a new file in [`REPLAY_FOLDER`], named after the current time.
+    pub fn save(&self) -> io::Result<()> {
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis());
+        fs::create_dir_all(REPLAY_FOLDER)?;
+        fs::write(Path::new(REPLAY_FOLDER).join(format!("replay_{}.txt", millis)), self.to_text())
+    }
+}
+
+/// The duration as `mm:ss`.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn parse_input(line: &str) -> Result<(u32, GameInput), String> {
+    let mut parts = line.split_whitespace();
+    let tick = parts.next().and_then(|tick| tick.parse::<u32>().ok());
+    let input = parts.next().and_then(GameInput::from_name);
+    match (tick, input) {
+        (Some(tick), Some(input)) => Ok((tick, input)),
+        _ => Err(format!("invalid input '{}'", line)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::replay::*;
+
+    #[test]
+    fn to_text_and_parse() {
+        let mut replay = Replay::new(GameMode::Master, 1234);
+        replay.push(GameInput::Left);
+        replay.ticks = 3;
+        replay.push(GameInput::Rotate);
+        replay.push(GameInput::Drop);
+        replay.push(GameInput::Hold);
+        replay.push(GameInput::RotateCcw);
+        replay.ticks = 10;
+
+        let text = replay.to_text();
+        assert!(text.starts_with("mode: Master\nseed: 1234\nticks: 10\ninputs:\n0 left\n3 rotate"));
+        assert_eq!(Replay::parse(&text), Ok(replay));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Replay::parse("mode: Classic\nseed: 1\ninputs:").is_err());
+        assert!(Replay::parse("mode: Nothing\nseed: 1\nticks: 1").is_err());
+        assert!(Replay::parse("mode: Classic\nseed: 1\nticks: 1\ninputs:\n2 jump").is_err());
+    }
+}
@@ -0,0 +1,273 @@
+//! Games left in the middle.
+
+use std::convert::TryInto;
+use crate::data::bit_board::BitBoard;
+use crate::data::bricks::Brick;
+use crate::data::game_panel::{LiveBrick, PanelSnapshot};
+use crate::data::master::MasterProgress;
+use crate::data::replay::Replay;
+use crate::game::MasterTimers;
+use crate::tetris::{GameMode, Point};
+
+/// The game is saved to this file.
+pub static SAVE_FILE: &str = "savegame.txt";
+
+/// A game left in the middle, bricks are written as their block offsets and the
+/// falling brick with its position first. The recording of the game follows the board:
+///
+/// ```text
+/// mode: Classic
+/// seed: 1234
+/// draws: 12
+/// score: 300
+/// lines: 2
+/// ticks: 240
+/// current: 3 5 1,0 0,1 1,1 2,1
+/// next: 0,0 0,1 1,0 1,1
+/// hold: 0,0 1,0 2,0 3,0
+/// can_hold: false
+/// board:
+/// ..........
+/// #########.
+/// replay:
+/// mode: Classic
+/// ...
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct SavedGame {
+    /// The mode of the game.
+    pub mode: GameMode,
+    /// The seed of the random bricks.
+    pub seed: u64,
+    /// Random bricks handed out from the seed.
+    pub draws: u32,
+    /// The score shown.
+    pub score: u32,
+    /// The cleared lines shown.
+    pub lines: u32,
+    /// The ticks played, the clock of the board.
+    pub ticks: u32,
+    /// The board with the falling brick.
+    pub panel: PanelSnapshot,
+    /// The brick after the falling one.
+    pub next_brick: Option<Brick>,
+    /// The held brick.
+    pub hold: Option<Brick>,
+    /// Whether the falling brick may still be held, only written when it may not.
+    pub can_hold: bool,
+    /// The progress in master mode.
+    pub master: Option<MasterProgress>,
+    /// The delays of master mode, where they were left.
+    pub master_timers: Option<MasterTimers>,
+    /// The recording of the game so far.
+    pub replay: Option<Replay>,
+}
+
+impl SavedGame {
+    /// The game in the format shown above.
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("mode: {}", self.mode.name()),
+            format!("seed: {}", self.seed),
+            format!("draws: {}", self.draws),
+            format!("score: {}", self.score),
+            format!("lines: {}", self.lines),
+            format!("ticks: {}", self.ticks),
+        ];
+        if let Some(live_brick) = self.panel.current_brick {
+            let position = live_brick.position;
+            lines.push(format!("current: {} {} {}", position.x, position.y, brick_to_text(&live_brick.brick)));
+        }
+        if let Some(brick) = &self.next_brick {
+            lines.push(format!("next: {}", brick_to_text(brick)));
+        }
+        if let Some(brick) = &self.hold {
+            lines.push(format!("hold: {}", brick_to_text(brick)));
+        }
+        if !self.can_hold {
+            lines.push(String::from("can_hold: false"));
+        }
+        if let Some(progress) = &self.master {
+            lines.push(format!("master: {}", progress.to_text()));
+        }
+        if let Some(timers) = &self.master_timers {
+            lines.push(format!("master_timers: {}", timers.to_text()));
+        }
+        lines.push(String::from("board:"));
+        lines.extend(self.panel.blocks.to_rows());
+        if let Some(replay) = &self.replay {
+            lines.push(String::from("replay:"));
+            lines.push(replay.to_text());
+        }
+        lines.join("\n")
+    }
+
+    /// Reads the text written by [`SavedGame::to_text`].
+    pub fn parse(content: &str) -> Result<SavedGame, String> {
+        let mut mode = None;
+        let mut seed = None;
+        let mut draws = None;
+        let mut score = None;
+        let mut lines = None;
+        let mut ticks = None;
+        let mut current_brick = None;
+        let mut next_brick = None;
+        let mut hold = None;
+        let mut can_hold = true;
+        let mut master = None;
+        let mut master_timers = None;
+        let mut board = Vec::new();
+        let mut replay = None;
+        let mut in_board = false;
+        let mut content_lines = content.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
+        while let Some(line) = content_lines.next() {
+            if line == "replay:" {
+                let rest: Vec<&str> = content_lines.by_ref().collect();
+                replay = Some(Replay::parse(&rest.join("\n"))?);
+                break;
+            }
+            if in_board {
+                board.push(line.to_string());
+                continue;
+            }
+            let (key, value) = match line.find(':') {
+                Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+                None => return Err(format!("expected 'key: value', found '{}'", line)),
+            };
+            match key {
+                "mode" => mode = Some(GameMode::from_name(value).ok_or(format!("unknown mode '{}'", value))?),
+                "seed" => seed = Some(parse_number::<u64>(value)?),
+                "draws" => draws = Some(parse_number::<u32>(value)?),
+                "score" => score = Some(parse_number::<u32>(value)?),
+                "lines" => lines = Some(parse_number::<u32>(value)?),
+                "ticks" => ticks = Some(parse_number::<u32>(value)?),
+                "current" => current_brick = Some(parse_live_brick(value)?),
+                "next" => next_brick = Some(parse_brick(value)?),
+                "hold" => hold = Some(parse_brick(value)?),
+                "can_hold" => can_hold = value.parse::<bool>().map_err(|_| format!("invalid flag '{}'", value))?,
+                "master" => master = Some(MasterProgress::parse(value)?),
+                "master_timers" => master_timers = Some(MasterTimers::parse(value)?),
+                "board" => in_board = true,
+                _ => return Err(format!("unknown key '{}'", key)),
+            }
+        }
+        Ok(SavedGame {
+            mode: mode.ok_or("missing mode")?,
+            seed: seed.ok_or("missing seed")?,
+            draws: draws.ok_or("missing draws")?,
+            score: score.ok_or("missing score")?,
+            lines: lines.ok_or("missing lines")?,
+            ticks: ticks.ok_or("missing ticks")?,
+            panel: PanelSnapshot {
+                blocks: BitBoard::from_rows(&board)?,
+                current_brick,
+            },
+            next_brick,
+            hold,
+            can_hold,
+            master,
+            master_timers,
+            replay,
+        })
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("invalid number '{}'", value))
+}
+
+fn brick_to_text(brick: &Brick) -> String {
+    let points: Vec<String> = brick.points.iter().map(|p| format!("{},{}", p.x, p.y)).collect();
+    points.join(" ")
+}
+
+fn parse_point(value: &str) -> Result<Point, String> {
+    let mut parts = value.split(',');
+    let x = parts.next().map(parse_number::<u16>).transpose()?;
+    let y = parts.next().map(parse_number::<u16>).transpose()?;
+    match (x, y, parts.next()) {
+        (Some(x), Some(y), None) => Ok(Point::new(x, y)),
+        _ => Err(format!("invalid point '{}'", value)),
+    }
+}
+
+fn parse_brick(value: &str) -> Result<Brick, String> {
+    let points = value.split_whitespace().map(parse_point).collect::<Result<Vec<Point>, String>>()?;
+    let points = points.try_into().map_err(|_| format!("a brick has 4 blocks, found '{}'", value))?;
+    Ok(Brick { points })
+}
+
+fn parse_live_brick(value: &str) -> Result<LiveBrick, String> {
+    let mut parts = value.splitn(3, ' ');
+    let x = parse_number::<u16>(parts.next().unwrap_or(""))?;
+    let y = parse_number::<u16>(parts.next().unwrap_or(""))?;
+    let brick = parse_brick(parts.next().unwrap_or(""))?;
+    Ok(LiveBrick {
+        brick,
+        position: Point::new(x, y),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::bit_board::BitBoard;
+    use crate::data::bricks::*;
+    use crate::data::game_panel::{LiveBrick, PanelSnapshot};
+    use crate::data::master::MasterProgress;
+    use crate::data::replay::{GameInput, Replay};
+    use crate::data::save::*;
+    use crate::game::{MasterPhase, MasterTimers};
+    use crate::tetris::{GameMode, Point, Size};
+
+    #[test]
+    fn to_text_and_parse() {
+        let mut blocks = BitBoard::new(Size { width: 10, height: 20 });
+        blocks.set(Point::new(0, 19), true);
+        let mut replay = Replay::new(GameMode::Master, 1234);
+        replay.push(GameInput::Drop);
+        replay.ticks = 100;
+        let game = SavedGame {
+            mode: GameMode::Master,
+            seed: 1234,
+            draws: 12,
+            score: 300,
+            lines: 2,
+            ticks: 240,
+            panel: PanelSnapshot {
+                blocks,
+                current_brick: Some(LiveBrick {
+                    brick: Brick::new(T_BRICK_POINTS),
+                    position: Point::new(3, 5),
+                }),
+            },
+            next_brick: Some(Brick::new(O_BRICK_POINTS)),
+            hold: Some(Brick::new(I_BRICK_POINTS)),
+            can_hold: false,
+            master: Some(MasterProgress::new()),
+            master_timers: Some(MasterTimers {
+                phase: MasterPhase::Are(12),
+                gravity_counter: 0,
+                lock_counter: 0,
+            }),
+            replay: Some(replay),
+        };
+
+        let text = game.to_text();
+        assert!(text.contains("\nticks: 240\ncurrent: 3 5 1,0 0,1 1,1 2,1\nnext: 0,0 0,1 1,0 1,1\n"));
+        assert!(text.contains("\nmaster_timers: are 12 0 0\n"));
+        assert_eq!(SavedGame::parse(&text), Ok(game));
+    }
+
+    #[test]
+    fn parse_without_brick_and_replay() {
+        let text = "mode: Classic\nseed: 1\ndraws: 0\nscore: 0\nlines: 0\nticks: 0\nboard:\n...\n#.#";
+        let game = SavedGame::parse(text).unwrap();
+        assert_eq!(game.panel.current_brick, None);
+        assert_eq!(game.replay, None);
+        assert_eq!(game.hold, None);
+        assert!(game.can_hold);
+        assert_eq!(game.master_timers, None);
+        assert!(game.panel.blocks.get(Point::new(2, 1)));
+        assert!(SavedGame::parse("mode: Classic\nseed: 1\ndraws: 0\nscore: 0\nlines: 0\nticks: 0\ncurrent: 1 1 0,0").is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! Versus games, where cleared lines send garbage rows to the other player.
+
+use std::collections::VecDeque;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::data::BlocksData;
+
+/// Garbage rows sent for clearing 0 to 4 lines.
+pub const ATTACK_TABLE: [u16; 5] = [0, 0, 1, 2, 4];
+/// Garbage rows sent for a T-spin clearing 0 to 3 lines.
+pub const T_SPIN_ATTACK_TABLE: [u16; 4] = [0, 2, 4, 6];
+
+/// The garbage rows a lock sends, for the lines it cleared and whether it was a T-spin.
+pub fn get_attack(lines: u16, t_spin: bool) -> u16 {
+    let table: &[u16] = if t_spin { &T_SPIN_ATTACK_TABLE } else { &ATTACK_TABLE };
+    table.get(lines as usize).copied().unwrap_or(table[table.len() - 1])
+}
+
+/// Where the holes of the garbage rows are.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum GarbageHoles {
+    /// One column for the whole game, the garbage can always be dug out straight down.
+    Aligned,
+    /// A random column for each attack, its rows have the hole in the same place.
+    PerAttack,
+    /// A random column for every row.
+    PerRow,
+}
+
+impl GarbageHoles {
+    /// All behaviours in the order they are offered.
+    pub const ALL: [GarbageHoles; 3] = [GarbageHoles::Aligned, GarbageHoles::PerAttack, GarbageHoles::PerRow];
+
+    /// The name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GarbageHoles::Aligned => "aligned",
+            GarbageHoles::PerAttack => "attack",
+            GarbageHoles::PerRow => "row",
+        }
+    }
+
+    /// The behaviour with the name written by [`GarbageHoles::name`].
+    pub fn from_name(name: &str) -> Option<GarbageHoles> {
+        GarbageHoles::ALL.iter().find(|holes| holes.name() == name).copied()
+    }
+}
+
+/// The garbage sent to and by one player of a versus game.
+#[derive(Clone, Debug)]
+pub struct VersusState {
+    /// The attacks received, oldest first, which rise before the next brick is put in.
+    pub pending: VecDeque<u16>,
+    /// Garbage rows left over after cancelling, to be sent to the other player.
+    pub outgoing: u16,
+    /// Where the holes of the garbage rows are.
+    pub holes: GarbageHoles,
+    // the column of aligned holes
+    aligned_hole: Option<u16>,
+    // the holes follow from the seed
+    rng: StdRng,
+}
+
+impl VersusState {
+    /// No garbage yet.
+    pub fn new(seed: u64, holes: GarbageHoles) -> VersusState {
+        VersusState {
+            pending: VecDeque::new(),
+            outgoing: 0,
+            holes,
+            aligned_hole: None,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// The garbage rows waiting to rise.
+    pub fn get_pending_rows(&self) -> u16 {
+        self.pending.iter().sum()
+    }
+
+    /// Adds garbage rows sent by the other player.
+    pub fn receive(&mut self, rows: u16) {
+        if rows > 0 {
+            self.pending.push_back(rows);
+        }
+    }
+
+    /// The attack of a lock cancels the oldest pending garbage first, the rest is sent out.
+    pub fn attack(&mut self, lines: u16, t_spin: bool) {
+        let mut attack = get_attack(lines, t_spin);
+        while attack > 0 {
+            match self.pending.front_mut() {
+                Some(rows) if *rows > attack => {
+                    *rows -= attack;
+                    attack = 0;
+                }
+                Some(rows) => {
+                    attack -= *rows;
+                    self.pending.pop_front();
+                }
+                None => break,
+            }
+        }
+        self.outgoing += attack;
+    }
+
+    /// Takes the garbage rows to send to the other player.
+    pub fn take_outgoing(&mut self) -> u16 {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    /// Pushes the pending garbage into the board from the bottom, the oldest attack first. Returns
+    /// whether blocks were pushed off the top.
+    pub fn insert_garbage(&mut self, blocks: &mut BlocksData) -> bool {
+        let width = blocks.get_size().width;
+        let mut overflow = false;
+        while let Some(rows) = self.pending.pop_front() {
+            let mut hole = self.get_attack_hole(width);
+            for _ in 0..rows {
+                if self.holes == GarbageHoles::PerRow {
+                    hole = self.rng.gen_range(0..width);
+                }
+                overflow |= blocks.push_garbage_line(hole);
+            }
+        }
+        overflow
+    }
+
+    fn get_attack_hole(&mut self, width: u16) -> u16 {
+        match (self.holes, self.aligned_hole) {
+            (GarbageHoles::Aligned, Some(hole)) => hole,
+            (GarbageHoles::Aligned, None) => {
+                let hole = self.rng.gen_range(0..width);
+                self.aligned_hole = Some(hole);
+                hole
+            }
+            _ => self.rng.gen_range(0..width),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::BlocksData;
+    use crate::data::versus::*;
+    use crate::tetris::{Point, Size};
+
+    #[test]
+    fn attack() {
+        assert_eq!(get_attack(1, false), 0);
+        assert_eq!(get_attack(4, false), 4);
+        assert_eq!(get_attack(2, true), 4);
+        assert_eq!(get_attack(0, true), 0);
+        // more lines than the table knows send the most
+        assert_eq!(get_attack(5, false), 4);
+    }
+
+    #[test]
+    fn insert_garbage() {
+        let mut state = VersusState::new(42, GarbageHoles::PerAttack);
+        let mut blocks = BlocksData::new(Size { width: 4, height: 3 });
+        blocks.set_region(Point::new(0, 2), Size { width: 1, height: 1 }, true);
+        state.receive(2);
+        assert_eq!(state.get_pending_rows(), 2);
+        assert!(!state.insert_garbage(&mut blocks));
+        assert_eq!(state.get_pending_rows(), 0);
+        assert_eq!(blocks.blocks[0], vec![true, false, false, false]);
+        // the rows of an attack have one hole in the same column
+        assert_eq!(blocks.blocks[1].iter().filter(|x| !**x).count(), 1);
+        assert_eq!(blocks.blocks[1], blocks.blocks[2]);
+
+        state.receive(1);
+        assert!(state.insert_garbage(&mut blocks));
+    }
+
+    fn get_holes(holes: GarbageHoles) -> Vec<usize> {
+        let mut state = VersusState::new(7, holes);
+        let mut blocks = BlocksData::new(Size { width: 10, height: 12 });
+        for _ in 0..4 {
+            state.receive(3);
+            assert!(!state.insert_garbage(&mut blocks));
+        }
+        blocks.blocks.iter().map(|line| line.iter().position(|x| !*x).unwrap()).collect()
+    }
+
+    #[test]
+    fn garbage_holes() {
+        let aligned = get_holes(GarbageHoles::Aligned);
+        assert!(aligned.iter().all(|hole| *hole == aligned[0]));
+        let per_attack = get_holes(GarbageHoles::PerAttack);
+        assert!(per_attack.chunks(3).all(|attack| attack.iter().all(|hole| *hole == attack[0])));
+        assert!(per_attack.iter().any(|hole| *hole != per_attack[0]));
+        let per_row = get_holes(GarbageHoles::PerRow);
+        assert!(per_row.chunks(3).any(|attack| attack.iter().any(|hole| *hole != attack[0])));
+        assert_eq!(GarbageHoles::from_name("row"), Some(GarbageHoles::PerRow));
+        assert_eq!(GarbageHoles::from_name("holes"), None);
+    }
+
+    #[test]
+    fn cancel_garbage() {
+        let mut state = VersusState::new(42, GarbageHoles::PerAttack);
+        state.receive(1);
+        state.receive(2);
+        // a double cancels the oldest attack, a tetris what is left and sends the rest
+        state.attack(2, false);
+        assert_eq!(state.pending, vec![2]);
+        assert_eq!(state.take_outgoing(), 0);
+        state.attack(4, false);
+        assert!(state.pending.is_empty());
+        assert_eq!(state.take_outgoing(), 2);
+        assert_eq!(state.take_outgoing(), 0);
+    }
+}
@@ -0,0 +1,756 @@
+//! One game, without any drawing or input handling.
+
+use crate::data::bricks::{Brick, BrickCollection};
+use crate::data::game_panel::{GamePanel, MoveDownResult, PutNewOneResult};
+use crate::data::master::{get_gravity, MasterProgress, GRAVITY_UNIT};
+use crate::data::practice::{Placement, PracticeState};
+use crate::data::puzzle::{Puzzle, PuzzleState};
+use crate::data::replay::GameInput;
+use crate::data::versus::{GarbageHoles, VersusState};
+use crate::tetris::{GameMode, MasterTiming, Movement, Size};
+
+/// The state of one game, it is driven by [`GameCore::next_frame`] once per tick and by
+/// [`GameCore::apply_input`] between ticks. With the same seed and the same inputs at the same
+/// ticks, a game always plays out the same way.
+pub struct GameCore {
+    /// The board with the falling brick.
+    pub manager: GamePanel,
+    /// Hands out the random bricks.
+    pub brick_collection: BrickCollection,
+    /// The brick put in after the falling one, `None` once a puzzle runs out of pieces.
+    pub next_brick: Option<Brick>,
+    /// Set in master mode.
+    pub master: Option<MasterState>,
+    /// Set while a puzzle is played.
+    pub puzzle: Option<PuzzleState>,
+    /// Set in practice mode.
+    pub practice: Option<PracticeState>,
+    /// Set in a versus game.
+    pub versus: Option<VersusState>,
+    /// The brick put aside with [`GameInput::Hold`], in its first rotation.
+    pub hold: Option<Brick>,
+    /// Whether the falling brick may still be held, a brick is held once until the next one comes in.
+    pub can_hold: bool,
+}
+
+/// What a tick did to the game.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub enum NextResult {
+    /// Nothing changed.
+    Waiting,
+    /// The falling brick moved down.
+    Success,
+    /// Lines were cleared, with the count and the new next brick.
+    LineClear(u16, Option<Brick>),
+    /// A new brick was put in, with the new next brick.
+    NewBrickPutIn(Option<Brick>),
+    /// The goal of the puzzle is reached.
+    PuzzleSolved,
+    /// No brick can be put in any more, or master mode is finished.
+    GameOver,
+}
+
+/// Where master mode is between two bricks, with the frames left.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub enum MasterPhase {
+    /// A brick is falling.
+    Falling,
+    /// Cleared lines are shown before ARE begins.
+    LineClear(u16),
+    /// The delay before the next brick appears.
+    Are(u16),
+}
+
+/// Master mode runs frame by frame with its own gravity and delays.
+pub struct MasterState {
+    /// The delays in frames.
+    pub timing: MasterTiming,
+    /// Level, score and section times.
+    pub progress: MasterProgress,
+    /// Where the game is between two bricks.
+    pub phase: MasterPhase,
+    gravity_counter: u32,
+    lock_counter: u16,
+}
+
+impl MasterState {
+    /// A new game waiting for its first brick.
+    pub fn new(timing: MasterTiming) -> MasterState {
+        MasterState {
+            timing,
+            progress: MasterProgress::new(),
+            phase: MasterPhase::Are(timing.are),
+            gravity_counter: 0,
+            lock_counter: 0,
+        }
+    }
+
+    /// The phase with the gravity and lock delay counters, to continue a saved game.
+    pub fn get_timers(&self) -> MasterTimers {
+        MasterTimers {
+            phase: self.phase,
+            gravity_counter: self.gravity_counter,
+            lock_counter: self.lock_counter,
+        }
+    }
+
+    /// Continues from the timers of [`MasterState::get_timers`].
+    pub fn set_timers(&mut self, timers: MasterTimers) {
+        self.phase = timers.phase;
+        self.gravity_counter = timers.gravity_counter;
+        self.lock_counter = timers.lock_counter;
+    }
+}
+
+/// Where master mode is in its delays, kept in a saved game.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub struct MasterTimers {
+    /// Where the game is between two bricks.
+    pub phase: MasterPhase,
+    /// The gravity gathered towards the next row.
+    pub gravity_counter: u32,
+    /// The frames the falling brick has been on the ground.
+    pub lock_counter: u16,
+}
+
+impl MasterTimers {
+    /// The phase as `falling`, `line_clear` or `are`, its frames left, the gravity and the lock
+    /// counter separated by spaces.
+    pub fn to_text(&self) -> String {
+        let (name, frames) = match self.phase {
+            MasterPhase::Falling => ("falling", 0),
+            MasterPhase::LineClear(frames) => ("line_clear", frames),
+            MasterPhase::Are(frames) => ("are", frames),
+        };
+        format!("{} {} {} {}", name, frames, self.gravity_counter, self.lock_counter)
+    }
+
+    /// Reads the text written by [`MasterTimers::to_text`].
+    pub fn parse(text: &str) -> Result<MasterTimers, String> {
+        let invalid = || format!("invalid master timers '{}'", text);
+        let parts: Vec<&str> = text.split_whitespace().collect();
+        if parts.len() != 4 {
+            return Err(invalid());
+        }
+        let frames = parts[1].parse::<u16>().map_err(|_| invalid())?;
+        let phase = match parts[0] {
+            "falling" => MasterPhase::Falling,
+            "line_clear" => MasterPhase::LineClear(frames),
+            "are" => MasterPhase::Are(frames),
+            _ => return Err(invalid()),
+        };
+        Ok(MasterTimers {
+            phase,
+            gravity_counter: parts[2].parse::<u32>().map_err(|_| invalid())?,
+            lock_counter: parts[3].parse::<u16>().map_err(|_| invalid())?,
+        })
+    }
+}
+
+
+impl GameCore {
+    /// A classic game with random bricks.
+    pub fn new(blocks_size: Size) -> GameCore {
+        let mut core = GameCore {
+            manager: GamePanel::new(blocks_size),
+            brick_collection: BrickCollection::new(),
+            next_brick: None,
+            master: None,
+            puzzle: None,
+            practice: None,
+            versus: None,
+            hold: None,
+            can_hold: true,
+        };
+        core.reset();
+        core
+    }
+
+    /// Starts over in the mode on an empty board, the random bricks follow from the seed.
+    pub fn start(&mut self, mode: GameMode, blocks_size: Size, timing: MasterTiming, seed: u64) {
+        self.brick_collection.set_seed(seed);
+        self.manager = GamePanel::new(blocks_size);
+        self.master = if mode == GameMode::Master {
+            Some(MasterState::new(timing))
+        } else {
+            None
+        };
+        self.puzzle = None;
+        self.practice = if mode == GameMode::Practice {
+            Some(PracticeState::new(blocks_size))
+        } else {
+            None
+        };
+        self.versus = if mode == GameMode::Versus {
+            Some(VersusState::new(seed, GarbageHoles::PerAttack))
+        } else {
+            None
+        };
+        self.reset();
+    }
+
+    /// Starts the puzzle on its board, fails if the board does not fit.
+    pub fn start_puzzle(&mut self, puzzle: &Puzzle, blocks_size: Size) -> Result<(), String> {
+        self.manager = GamePanel::new(blocks_size);
+        self.manager.load_board(&puzzle.board)?;
+        self.master = None;
+        self.practice = None;
+        self.versus = None;
+        self.puzzle = Some(PuzzleState::new(puzzle));
+        self.next_brick = self.get_new_brick();
+        self.hold = None;
+        self.can_hold = true;
+        Ok(())
+    }
+
+    /// Moves the falling brick down, or locks it and puts in the next one.
+    pub fn fall(&mut self) -> NextResult {
+        // move
+        let down = self.manager.move_down();
+        match down {
+            MoveDownResult::NeedNewOne => {
+                let line_clear_result = self.manager.try_clean_lines();
+                if let Some(puzzle) = self.puzzle.as_mut() {
+                    let lines = line_clear_result.as_ref().map_or(0, |r| r.clear_lines.len() as u16);
+                    if puzzle.on_lock(lines, self.manager.last_lock_t_spin) {
+                        return NextResult::PuzzleSolved;
+                    }
+                }
+                let next_brick = match self.next_brick {
+                    Some(brick) => brick,
+                    // a puzzle ran out of pieces
+                    None => return NextResult::GameOver,
+                };
+                if let Some(versus) = self.versus.as_mut() {
+                    // cleared lines hold the garbage back, it rises after a lock which clears none
+                    match &line_clear_result {
+                        Some(result) => versus.attack(result.clear_lines.len() as u16, self.manager.last_lock_t_spin),
+                        None => {
+                            if versus.insert_garbage(&mut self.manager.blocks) {
+                                return NextResult::GameOver;
+                            }
+                        }
+                    }
+                }
+                let put_new_one_result = self.manager.put_new_one(&next_brick);
+                match put_new_one_result {
+                    PutNewOneResult::Success => {
+                        let new_brick = self.get_new_brick();
+                        self.next_brick = new_brick;
+                        self.can_hold = true;
+                        if let Some(practice) = self.practice.as_mut() {
+                            practice.on_put_in(Placement {
+                                panel: self.manager.snapshot(),
+                                next_brick: new_brick,
+                                queue: practice.queue.clone(),
+                                hold: self.hold,
+                            });
+                        }
+                        match line_clear_result {
+                            Some(result) => NextResult::LineClear(result.clear_lines.len() as u16, new_brick),
+                            None => NextResult::NewBrickPutIn(new_brick),
+                        }
+                    }
+                    PutNewOneResult::GameOver => NextResult::GameOver,
+                }
+            }
+            MoveDownResult::Success => {
+                NextResult::Success
+            }
+        }
+    }
+
+    /// Puzzles hand out their fixed pieces, practice its chosen ones first, other modes random ones.
+    pub fn get_new_brick(&mut self) -> Option<Brick> {
+        let collection = &mut self.brick_collection;
+        if let Some(puzzle) = self.puzzle.as_mut() {
+            return puzzle.pieces.pop_front().map(|brick_type| collection.get_new_one(brick_type, 0));
+        }
+        if let Some(brick_type) = self.practice.as_mut().and_then(|practice| practice.queue.pop_front()) {
+            return Some(collection.get_new_one(brick_type, 0));
+        }
+        Some(collection.get_rand_one())
+    }
+
+    /// Goes back to when the last placed brick was put in, returns whether anything was taken back.
+    pub fn undo(&mut self) -> bool {
+        let placement = self.practice.as_mut().and_then(|practice| practice.undo());
+        self.restore_placement(placement)
+    }
+
+    /// Puts back the placement taken back by the last undo, returns whether there was one.
+    pub fn redo(&mut self) -> bool {
+        let placement = self.practice.as_mut().and_then(|practice| practice.redo());
+        self.restore_placement(placement)
+    }
+
+    fn restore_placement(&mut self, placement: Option<Placement>) -> bool {
+        let placement = match placement {
+            Some(placement) => placement,
+            None => return false,
+        };
+        self.manager.restore(&placement.panel);
+        self.next_brick = placement.next_brick;
+        self.hold = placement.hold;
+        self.can_hold = true;
+        if let Some(practice) = self.practice.as_mut() {
+            practice.queue = placement.queue;
+        }
+        true
+    }
+
+    /// Goes back to the saved setup in practice mode.
+    pub fn reset_practice(&mut self) {
+        if let Some(practice) = self.practice.as_mut() {
+            let blocks = practice.restore_setup();
+            self.manager.restore_blocks(blocks);
+            self.next_brick = self.get_new_brick();
+            self.hold = None;
+            self.can_hold = true;
+        }
+    }
+
+    /// Advances the game by one tick, a frame in master mode, otherwise the same as [`GameCore::fall`].
+    /// The board counts the ticks.
+    pub fn next_frame(&mut self) -> NextResult {
+        self.manager.blocks.tick += 1;
+        let state = match self.master.as_mut() {
+            Some(state) => state,
+            None => return self.fall(),
+        };
+        state.progress.tick();
+        let mut result = NextResult::Waiting;
+        match state.phase {
+            MasterPhase::LineClear(frames) if frames > 1 => {
+                state.phase = MasterPhase::LineClear(frames - 1);
+                return result;
+            }
+            MasterPhase::LineClear(_) => {
+                state.phase = MasterPhase::Are(state.timing.are);
+                return result;
+            }
+            MasterPhase::Are(frames) if frames > 1 => {
+                state.phase = MasterPhase::Are(frames - 1);
+                return result;
+            }
+            MasterPhase::Are(_) => {
+                if self.manager.put_new_one(&self.next_brick.unwrap()) == PutNewOneResult::GameOver {
+                    return NextResult::GameOver;
+                }
+                let new_brick = self.brick_collection.get_rand_one();
+                self.next_brick = Some(new_brick);
+                self.can_hold = true;
+                state.progress.on_spawn();
+                state.phase = MasterPhase::Falling;
+                state.gravity_counter = 0;
+                state.lock_counter = 0;
+                result = NextResult::NewBrickPutIn(Some(new_brick));
+            }
+            MasterPhase::Falling => {}
+        }
+
+        // gravity, a spawned brick falls in the same frame
+        state.gravity_counter += get_gravity(state.progress.level);
+        while state.gravity_counter >= GRAVITY_UNIT {
+            state.gravity_counter -= GRAVITY_UNIT;
+            if self.manager.move_current_brick(Movement { x: 0, y: 1 }) {
+                state.lock_counter = 0;
+                if result == NextResult::Waiting {
+                    result = NextResult::Success;
+                }
+            } else {
+                state.gravity_counter = 0;
+            }
+        }
+
+        if self.manager.is_current_brick_landed() {
+            state.lock_counter += 1;
+            if state.lock_counter >= state.timing.lock_delay {
+                self.manager.move_down();
+                let lines = self.manager.try_clean_lines().map_or(0, |result| result.clear_lines.len() as u16);
+                let finished = state.progress.on_lock(lines, lines > 0 && self.manager.blocks.is_empty());
+                if finished {
+                    return NextResult::GameOver;
+                }
+                return if lines > 0 {
+                    state.phase = MasterPhase::LineClear(state.timing.line_clear_delay);
+                    NextResult::LineClear(lines, self.next_brick)
+                } else {
+                    state.phase = MasterPhase::Are(state.timing.are);
+                    NextResult::Success
+                };
+            }
+        }
+        result
+    }
+
+    /// Moves, rotates or holds the falling brick.
+    pub fn apply_input(&mut self, input: GameInput) {
+        match input {
+            GameInput::Left => self.move_left(),
+            GameInput::Right => self.move_right(),
+            GameInput::Rotate => self.rotate(3),
+            GameInput::Drop => self.move_bottom(),
+            GameInput::SoftDrop => self.move_down(),
+            GameInput::RotateCcw => self.rotate(1),
+            GameInput::Rotate180 => self.rotate(2),
+            GameInput::Hold => self.hold(),
+        }
+    }
+
+    fn rotate(&mut self, times: usize) {
+        self.manager.rotate_current_brick_times(times)
+    }
+
+    // unlike a tick, moving down never locks the brick
+    fn move_down(&mut self) {
+        self.manager.move_current_brick(Movement { x: 0, y: 1 });
+    }
+
+    // the first hold takes the next brick, later ones swap with the held brick
+    fn hold(&mut self) {
+        if !self.can_hold {
+            return;
+        }
+        let brick_type = match self.manager.current_brick.and_then(|live_brick| self.brick_collection.find_type(&live_brick.brick)) {
+            Some(brick_type) => brick_type,
+            None => return,
+        };
+        let new_brick = match self.hold.or(self.next_brick) {
+            Some(brick) => brick,
+            None => return,
+        };
+        if self.manager.swap_current_brick(&new_brick) == PutNewOneResult::GameOver {
+            return;
+        }
+        if self.hold.is_none() {
+            self.next_brick = self.get_new_brick();
+        }
+        self.hold = Some(self.brick_collection.get_new_one(brick_type, 0));
+        self.can_hold = false;
+        if let Some(state) = self.master.as_mut() {
+            state.gravity_counter = 0;
+            state.lock_counter = 0;
+        }
+    }
+
+    fn move_left(&mut self) {
+        self.manager.move_current_brick_to_left()
+    }
+
+    fn move_right(&mut self) {
+        self.manager.move_current_brick_to_right();
+    }
+
+    fn move_bottom(&mut self) {
+        self.manager.move_current_brick_to_bottom()
+    }
+
+    /// Clears the board and draws a new next brick.
+    pub fn reset(&mut self) {
+        self.manager.reset();
+        let brick = self.brick_collection.get_rand_one();
+        self.next_brick = Some(brick);
+        self.hold = None;
+        self.can_hold = true;
+        if let Some(state) = self.master.as_mut() {
+            *state = MasterState::new(state.timing);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::bricks::*;
+    use crate::data::practice::PracticeState;
+    use crate::data::puzzle::{Puzzle, PuzzleState};
+    use crate::data::replay::{GameInput, Replay};
+    use crate::game::*;
+    use crate::tetris::*;
+
+    static TEST_SIZE: Size = Size {
+        width: 10,
+        height: 20,
+    };
+
+
+    static TEST_TIMING: MasterTiming = MasterTiming {
+        are: 30,
+        line_clear_delay: 41,
+        lock_delay: 30,
+    };
+
+    // a classic game on the test board which puts in the brick first
+    fn new_core(next_brick: Option<Brick>) -> GameCore {
+        let mut core = GameCore::new(TEST_SIZE);
+        core.next_brick = next_brick;
+        core
+    }
+
+    #[test]
+    fn fall() {
+        let mut core = GameCore::new(TEST_SIZE);
+
+        let result = core.fall();
+        assert!(matches!(result, NextResult::NewBrickPutIn(_)));
+
+        let panel = &core.manager;
+        let brick = &panel.current_brick.unwrap();
+        assert_eq!(brick.position.y, 0);
+        assert_ne!(brick.position.x, 0);
+    }
+
+    #[test]
+    fn big_mode_panel() {
+        let blocks_size = GameMode::Big.get_blocks_size(TEST_SIZE);
+        assert_eq!(blocks_size, Size { width: 5, height: 10 });
+
+        let mut core = GameCore::new(blocks_size);
+        core.next_brick = Some(Brick::new(I_BRICK_POINTS));
+        core.fall();
+        for _i in 0..10 {
+            core.move_right();
+        }
+        let brick = &core.manager.current_brick.unwrap();
+        assert_eq!(brick.position.x, 1);
+
+        core.move_bottom();
+        let bottom = blocks_size.height as usize - 1;
+        assert!(core.manager.blocks.blocks[bottom][1..5].iter().all(|x| *x));
+    }
+
+    #[test]
+    fn master_mode_frames() {
+        let timing = TEST_TIMING;
+        let mut core = new_core(Some(Brick::new(O_BRICK_POINTS)));
+        core.master = Some(MasterState::new(timing));
+
+        // nothing happens during ARE
+        for _i in 1..timing.are {
+            assert_eq!(core.next_frame(), NextResult::Waiting);
+        }
+        match core.next_frame() {
+            NextResult::NewBrickPutIn(_) => {}
+            result => panic!("unexpected {:?}", result),
+        }
+        assert_eq!(core.master.as_ref().unwrap().progress.level, 1);
+
+        // 20G brings the brick to the floor at once, then it locks after the lock delay
+        core.master.as_mut().unwrap().progress.level = 500;
+        core.next_frame();
+        assert!(core.manager.is_current_brick_landed());
+        for _i in 1..timing.lock_delay {
+            core.next_frame();
+        }
+        assert!(core.manager.current_brick.is_none());
+        assert_eq!(core.master.as_ref().unwrap().phase, MasterPhase::Are(timing.are));
+    }
+
+    #[test]
+    fn master_timers_to_text_and_parse() {
+        let timing = TEST_TIMING;
+        let mut core = new_core(Some(Brick::new(O_BRICK_POINTS)));
+        core.master = Some(MasterState::new(timing));
+        for _i in 0..timing.are + 3 {
+            core.next_frame();
+        }
+        let timers = core.master.as_ref().unwrap().get_timers();
+        assert_eq!(timers.phase, MasterPhase::Falling);
+        assert!(timers.gravity_counter > 0);
+
+        // a saved game goes on where its delays were left
+        let mut state = MasterState::new(timing);
+        state.set_timers(MasterTimers::parse(&timers.to_text()).unwrap());
+        assert_eq!(state.get_timers(), timers);
+        let line_clear = MasterTimers { phase: MasterPhase::LineClear(7), gravity_counter: 0, lock_counter: 0 };
+        assert_eq!(line_clear.to_text(), "line_clear 7 0 0");
+        assert_eq!(MasterTimers::parse(&line_clear.to_text()), Ok(line_clear));
+        assert!(MasterTimers::parse("waiting 1 0 0").is_err());
+        assert!(MasterTimers::parse("are 1 0").is_err());
+    }
+
+    #[test]
+    fn puzzle_solved() {
+        let puzzle = &Puzzle::get_built_in()[0];
+        let mut core = new_core(None);
+        core.puzzle = Some(PuzzleState::new(puzzle));
+        core.manager.load_board(&puzzle.board).unwrap();
+        core.next_brick = core.get_new_brick();
+
+        assert_eq!(core.fall(), NextResult::NewBrickPutIn(None));
+        core.rotate(1);
+        for _i in 0..TEST_SIZE.width {
+            core.move_right();
+        }
+        core.move_bottom();
+        assert_eq!(core.fall(), NextResult::PuzzleSolved);
+    }
+
+    #[test]
+    fn puzzle_out_of_pieces() {
+        let puzzle = &Puzzle::get_built_in()[0];
+        let mut core = new_core(None);
+        core.puzzle = Some(PuzzleState::new(puzzle));
+        core.manager.load_board(&puzzle.board).unwrap();
+        core.next_brick = core.get_new_brick();
+
+        core.fall();
+        core.move_bottom();
+        assert_eq!(core.fall(), NextResult::GameOver);
+    }
+
+    #[test]
+    fn practice_undo_and_reset() {
+        let mut core = new_core(None);
+        core.practice = Some(PracticeState::new(TEST_SIZE));
+        let practice = core.practice.as_mut().unwrap();
+        practice.queue.extend([BrickType::O, BrickType::I, BrickType::T]);
+        practice.save_setup(&core.manager.blocks);
+        core.next_brick = core.get_new_brick();
+
+        core.fall();
+        assert!(!core.undo());
+        core.move_bottom();
+        core.fall();
+        core.move_bottom();
+        core.fall();
+        let t_brick = core.manager.current_brick.unwrap();
+        assert_eq!(t_brick.brick, core.brick_collection.get_new_one(BrickType::T, 0));
+
+        // act
+        assert!(core.undo());
+        assert!(core.undo());
+        assert_eq!(core.manager.current_brick.unwrap().brick, Brick::new(O_BRICK_POINTS));
+        assert_eq!(core.next_brick, Some(core.brick_collection.get_new_one(BrickType::I, 0)));
+        assert!(core.manager.blocks.blocks[TEST_SIZE.height as usize - 1].iter().all(|x| !*x));
+        assert!(!core.undo());
+
+        assert!(core.redo());
+        assert!(core.redo());
+        assert_eq!(core.manager.current_brick, Some(t_brick));
+        assert!(!core.redo());
+
+        core.move_bottom();
+        core.fall();
+        core.reset_practice();
+        assert!(core.manager.blocks.is_empty());
+        assert!(core.manager.current_brick.is_none());
+        assert_eq!(core.next_brick, Some(Brick::new(O_BRICK_POINTS)));
+        assert_eq!(core.practice.as_ref().unwrap().get_queue_text(), "IT");
+    }
+
+    #[test]
+    fn hold_once_per_brick() {
+        let mut core = GameCore::new(TEST_SIZE);
+        core.next_brick = Some(Brick::new(O_BRICK_POINTS));
+        core.fall();
+        core.apply_input(GameInput::SoftDrop);
+        assert_eq!(core.manager.current_brick.unwrap().position.y, 1);
+        let next_brick = core.next_brick;
+
+        // the first hold takes the next brick
+        core.apply_input(GameInput::Hold);
+        assert_eq!(core.hold, Some(Brick::new(O_BRICK_POINTS)));
+        assert_eq!(core.manager.current_brick.map(|b| b.brick), next_brick);
+        assert_eq!(core.manager.current_brick.unwrap().position.y, 0);
+        assert_ne!(core.next_brick, None);
+
+        // holding again has to wait for the next brick
+        core.apply_input(GameInput::Hold);
+        assert_eq!(core.hold, Some(Brick::new(O_BRICK_POINTS)));
+        core.apply_input(GameInput::Drop);
+        core.fall();
+        let current = core.manager.current_brick.unwrap().brick;
+        core.apply_input(GameInput::Hold);
+        assert_eq!(core.manager.current_brick.unwrap().brick, Brick::new(O_BRICK_POINTS));
+        assert_eq!(core.hold.and_then(|b| core.brick_collection.find_type(&b)), core.brick_collection.find_type(&current));
+    }
+
+    #[test]
+    fn rotate_both_ways() {
+        let mut core = GameCore::new(TEST_SIZE);
+        core.next_brick = Some(Brick::new(T_BRICK_POINTS));
+        core.fall();
+        core.apply_input(GameInput::SoftDrop);
+        core.apply_input(GameInput::RotateCcw);
+        core.apply_input(GameInput::Rotate);
+        assert_eq!(core.manager.current_brick.unwrap().brick, Brick::new(T_BRICK_POINTS));
+        core.apply_input(GameInput::Rotate180);
+        core.apply_input(GameInput::Rotate);
+        core.apply_input(GameInput::Rotate);
+        assert_eq!(core.manager.current_brick.unwrap().brick, Brick::new(T_BRICK_POINTS));
+
+        // the T points up, a clockwise turn points it right
+        core.apply_input(GameInput::Rotate);
+        let mut points = core.manager.current_brick.unwrap().brick.points;
+        points.sort_by_key(|p| (p.y, p.x));
+        assert_eq!(points, [Point::new(0, 0), Point::new(0, 1), Point::new(1, 1), Point::new(0, 2)]);
+    }
+
+    #[test]
+    fn versus_garbage() {
+        let mut core = GameCore::new(TEST_SIZE);
+        core.start(GameMode::Versus, TEST_SIZE, TEST_TIMING, 42);
+        core.fall();
+        core.versus.as_mut().unwrap().receive(2);
+        core.apply_input(GameInput::Drop);
+        let bottom = TEST_SIZE.height as usize - 1;
+        assert!(core.manager.blocks.blocks[bottom].iter().any(|x| *x));
+
+        // the garbage rises under the locked brick before the next one comes in
+        assert!(matches!(core.fall(), NextResult::NewBrickPutIn(_)));
+        assert_eq!(core.versus.as_ref().unwrap().get_pending_rows(), 0);
+        assert_eq!(core.manager.blocks.blocks[bottom].iter().filter(|x| !**x).count(), 1);
+        assert_eq!(core.manager.blocks.blocks[bottom - 1].iter().filter(|x| !**x).count(), 1);
+
+        // a line clear cancels garbage instead of letting it rise
+        let size = core.manager.blocks.get_size();
+        core.manager.blocks.set_region(Point::new(0, size.height - 4), Size { width: size.width, height: 2 }, true);
+        core.versus.as_mut().unwrap().receive(3);
+        core.apply_input(GameInput::Drop);
+        assert!(matches!(core.fall(), NextResult::LineClear(2, _)));
+        assert_eq!(core.versus.as_ref().unwrap().get_pending_rows(), 2);
+        assert_eq!(core.versus.as_mut().unwrap().take_outgoing(), 0);
+
+        // too much garbage pushes the stack off the board
+        core.versus.as_mut().unwrap().receive(TEST_SIZE.height);
+        core.apply_input(GameInput::Drop);
+        assert_eq!(core.fall(), NextResult::GameOver);
+    }
+
+    fn play(replay: &Replay) -> GameCore {
+        let mut core = new_core(None);
+        core.brick_collection.set_seed(replay.seed);
+        core.reset();
+        let mut inputs = replay.inputs.iter().peekable();
+        for tick in 0..replay.ticks {
+            while let Some((_, input)) = inputs.next_if(|(input_tick, _)| *input_tick == tick) {
+                core.apply_input(*input);
+            }
+            core.next_frame();
+        }
+        core
+    }
+
+    #[test]
+    fn replay_is_deterministic() {
+        let mut replay = Replay::new(GameMode::Classic, 7);
+        for tick in 0..60 {
+            replay.ticks = tick;
+            match tick % 4 {
+                0 => replay.push(GameInput::Left),
+                1 => replay.push(GameInput::Rotate),
+                2 => replay.push(GameInput::Drop),
+                _ => replay.push(GameInput::Hold),
+            }
+        }
+        replay.ticks = 60;
+
+        let core = play(&replay);
+        let other = play(&replay);
+        assert!(!core.manager.blocks.is_empty());
+        assert_eq!(core.manager.blocks.blocks, other.manager.blocks.blocks);
+        assert_eq!(core.manager.current_brick, other.manager.current_brick);
+        assert_eq!(core.next_brick, other.next_brick);
+    }
+}
@@ -0,0 +1,26 @@
+//! The game logic of Newbe.Tetris without any terminal or audio.
+//!
+//! [`game::GameCore`] holds a whole game and runs it tick by tick, the types it is built from
+//! live in [`data`], and [`bot::Bot`] plays a game by itself. Games are deterministic: the random
+//! bricks follow from a seed, so a game can be replayed from the seed and the inputs of a
+//! [`data::replay::Replay`].
+//!
+//! ```
+//! use tetris_engine::data::replay::GameInput;
+//! use tetris_engine::game::{GameCore, NextResult};
+//! use tetris_engine::tetris::{GameMode, MasterTiming, Size};
+//!
+//! let size = Size { width: 10, height: 20 };
+//! let timing = MasterTiming { are: 30, line_clear_delay: 41, lock_delay: 30 };
+//! let mut core = GameCore::new(size);
+//! core.start(GameMode::Classic, size, timing, 42);
+//! assert!(matches!(core.next_frame(), NextResult::NewBrickPutIn(_)));
+//! core.apply_input(GameInput::Drop);
+//! ```
+
+#![warn(missing_docs)]
+
+pub mod bot;
+pub mod data;
+pub mod game;
+pub mod tetris;
@@ -0,0 +1,148 @@
+//! Sizes, positions and game modes.
+
+use std::time::Duration;
+use crate::data::master::FRAMES_PER_SECOND;
+
+/// A size in cells or blocks.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Size {
+    /// Rows.
+    pub height: u16,
+    /// Columns.
+    pub width: u16,
+}
+
+/// A position, `y` grows downwards.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Point {
+    /// Column.
+    pub x: u16,
+    /// Row.
+    pub y: u16,
+}
+
+/// A move by some columns and rows.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Movement {
+    /// Columns, negative to the left.
+    pub x: i16,
+    /// Rows, negative upwards.
+    pub y: i16,
+}
+
+impl Point {
+    /// A point at the column and row.
+    pub fn new(x: u16, y: u16) -> Point {
+        Point { x, y }
+    }
+}
+
+/// The ways to play.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum GameMode {
+    /// Bricks fall every 500 ms.
+    Classic,
+    /// Locked blocks disappear right after locking.
+    Invisible,
+    /// Locked blocks fade out over a few seconds.
+    Fading,
+    /// Every block takes up 2x2 cells on a board with half the logical width.
+    Big,
+    /// Gravity ramps up to 20G with ARE, line clear delay and lock delay.
+    Master,
+    /// Reach a goal on a prepared board with fixed pieces.
+    Puzzle,
+    /// Edit the board and the next pieces, take back placements.
+    Practice,
+    /// Two players side by side, cleared lines send garbage rows to the other one.
+    Versus,
+    /// The computer plays a classic game by itself.
+    Demo,
+}
+
+impl GameMode {
+    /// All modes in the order they are offered.
+    pub const ALL: [GameMode; 9] = [
+        GameMode::Classic, GameMode::Invisible, GameMode::Fading, GameMode::Big, GameMode::Master, GameMode::Puzzle,
+        GameMode::Practice, GameMode::Versus, GameMode::Demo,
+    ];
+
+    /// The name shown and written to files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Invisible => "Invisible",
+            GameMode::Fading => "Fading",
+            GameMode::Big => "Big",
+            GameMode::Master => "Master",
+            GameMode::Puzzle => "Puzzle",
+            GameMode::Practice => "Practice",
+            GameMode::Versus => "Versus",
+            GameMode::Demo => "Demo",
+        }
+    }
+
+    /// The mode with the name written by [`GameMode::name`].
+    pub fn from_name(name: &str) -> Option<GameMode> {
+        GameMode::ALL.iter().find(|mode| mode.name() == name).copied()
+    }
+
+    /// Whether locked blocks are hidden from the player.
+    pub fn hides_stack(&self) -> bool {
+        match self {
+            GameMode::Classic | GameMode::Big | GameMode::Master | GameMode::Puzzle | GameMode::Practice
+            | GameMode::Versus | GameMode::Demo => false,
+            GameMode::Invisible | GameMode::Fading => true,
+        }
+    }
+
+    /// How many cells a block takes up in each direction.
+    pub fn block_scale(&self) -> u16 {
+        match self {
+            GameMode::Big => 2,
+            _ => 1,
+        }
+    }
+
+    /// Frames of [`FRAMES_PER_SECOND`] between two ticks, outside master mode gravity moves the brick one row
+    /// per tick. Master mode runs frame by frame, puzzles and practice leave time to think, other modes move down
+    /// every 30 frames.
+    pub fn frames_per_tick(&self) -> u32 {
+        match self {
+            GameMode::Master => 1,
+            GameMode::Puzzle | GameMode::Practice => 60,
+            _ => 30,
+        }
+    }
+
+    /// How long a tick lasts.
+    pub fn tick_duration(&self) -> Duration {
+        Duration::from_secs(1) * self.frames_per_tick() / FRAMES_PER_SECOND
+    }
+
+    /// Puzzles and practice change the board outside of the game, so they are not recorded, and
+    /// neither is a versus game with its two players nor a game nobody played.
+    pub fn is_recordable(&self) -> bool {
+        !matches!(self, GameMode::Puzzle | GameMode::Practice | GameMode::Versus | GameMode::Demo)
+    }
+
+    /// The logical board size for a board of `gaming_blocks_size` cells.
+    pub fn get_blocks_size(&self, gaming_blocks_size: Size) -> Size {
+        let scale = self.block_scale();
+        Size {
+            height: gaming_blocks_size.height / scale,
+            width: gaming_blocks_size.width / scale,
+        }
+    }
+}
+
+/// Delays of master mode, in frames.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct MasterTiming {
+    /// Wait before the next brick appears.
+    pub are: u16,
+    /// Wait after lines are cleared.
+    pub line_clear_delay: u16,
+    /// How long a brick may rest on the stack before it locks.
+    pub lock_delay: u16,
+}
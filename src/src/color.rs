@@ -0,0 +1,146 @@
+use std::env;
+use crossterm::style::Color;
+
+// the colors the terminal can show, from the fewest to the most
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum ColorSupport {
+    // the 16 named colors
+    Basic,
+    Ansi256,
+    TrueColor,
+}
+
+// the 16 named colors in the order of their ansi values, with the rgb of the xterm palette
+static BASIC_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (205, 0, 0)),
+    (Color::DarkGreen, (0, 205, 0)),
+    (Color::DarkYellow, (205, 205, 0)),
+    (Color::DarkBlue, (0, 0, 238)),
+    (Color::DarkMagenta, (205, 0, 205)),
+    (Color::DarkCyan, (0, 205, 205)),
+    (Color::Grey, (229, 229, 229)),
+    (Color::DarkGrey, (127, 127, 127)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (92, 92, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// the levels of each channel in the 6x6x6 cube of the 256 colors
+static CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorSupport {
+    pub fn detect() -> ColorSupport {
+        // the windows console has true colors since windows 10
+        if cfg!(windows) {
+            return ColorSupport::TrueColor;
+        }
+        ColorSupport::from_env(env::var("COLORTERM").ok().as_deref(), env::var("TERM").ok().as_deref())
+    }
+
+    fn from_env(colorterm: Option<&str>, term: Option<&str>) -> ColorSupport {
+        match (colorterm, term) {
+            (Some("truecolor"), _) | (Some("24bit"), _) => ColorSupport::TrueColor,
+            (_, Some(term)) if term.contains("256color") => ColorSupport::Ansi256,
+            _ => ColorSupport::Basic,
+        }
+    }
+
+    // the nearest color the terminal can show
+    pub fn downsample(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorSupport::TrueColor, _) => color,
+            (ColorSupport::Ansi256, Color::Rgb { r, g, b }) => nearest_ansi_value((r, g, b)),
+            (ColorSupport::Basic, Color::Rgb { .. }) | (ColorSupport::Basic, Color::AnsiValue(_)) => {
+                let rgb = to_rgb(color);
+                BASIC_COLORS.iter().min_by_key(|(_, basic)| distance(*basic, rgb)).unwrap().0
+            }
+            _ => color,
+        }
+    }
+}
+
+// the color half way to white
+pub fn lighten(color: Color) -> Color {
+    let (r, g, b) = to_rgb(color);
+    let lighten_channel = |channel: u8| channel + (255 - channel) / 2;
+    Color::Rgb { r: lighten_channel(r), g: lighten_channel(g), b: lighten_channel(b) }
+}
+
+fn to_rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Rgb { r, g, b } => (r, g, b),
+        Color::AnsiValue(value) if value < 16 => BASIC_COLORS[value as usize].1,
+        Color::AnsiValue(value) if value < 232 => {
+            let index = value - 16;
+            (CUBE_LEVELS[(index / 36) as usize], CUBE_LEVELS[(index / 6 % 6) as usize], CUBE_LEVELS[(index % 6) as usize])
+        }
+        Color::AnsiValue(value) => {
+            let grey = 8 + (value - 232) * 10;
+            (grey, grey, grey)
+        }
+        _ => BASIC_COLORS.iter()
+            .find(|(basic, _)| *basic == color)
+            .map(|(_, rgb)| *rgb)
+            .unwrap_or((0, 0, 0)),
+    }
+}
+
+// the nearest of the color cube and the grey ramp
+fn nearest_ansi_value(rgb: (u8, u8, u8)) -> Color {
+    let nearest_level = |channel: u8| (0..CUBE_LEVELS.len())
+        .min_by_key(|i| (CUBE_LEVELS[*i] as i32 - channel as i32).abs())
+        .unwrap() as u8;
+    let cube = 16 + 36 * nearest_level(rgb.0) + 6 * nearest_level(rgb.1) + nearest_level(rgb.2);
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let grey = 232 + ((average.saturating_sub(3) / 10).min(23)) as u8;
+    if distance(to_rgb(Color::AnsiValue(grey)), rgb) < distance(to_rgb(Color::AnsiValue(cube)), rgb) {
+        Color::AnsiValue(grey)
+    } else {
+        Color::AnsiValue(cube)
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    channel(a.0, b.0) + channel(a.1, b.1) + channel(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Color;
+    use crate::color::*;
+
+    #[test]
+    fn detect() {
+        assert_eq!(ColorSupport::from_env(Some("truecolor"), Some("xterm-256color")), ColorSupport::TrueColor);
+        assert_eq!(ColorSupport::from_env(Some("24bit"), None), ColorSupport::TrueColor);
+        assert_eq!(ColorSupport::from_env(None, Some("screen-256color")), ColorSupport::Ansi256);
+        assert_eq!(ColorSupport::from_env(None, Some("xterm")), ColorSupport::Basic);
+        assert_eq!(ColorSupport::from_env(None, None), ColorSupport::Basic);
+    }
+
+    #[test]
+    fn downsample() {
+        let orange = Color::Rgb { r: 255, g: 135, b: 0 };
+        assert_eq!(ColorSupport::TrueColor.downsample(orange), orange);
+        assert_eq!(ColorSupport::Ansi256.downsample(orange), Color::AnsiValue(208));
+        assert_eq!(ColorSupport::Ansi256.downsample(Color::Rgb { r: 100, g: 100, b: 100 }), Color::AnsiValue(241));
+        assert_eq!(ColorSupport::Basic.downsample(Color::Rgb { r: 250, g: 10, b: 10 }), Color::Red);
+        assert_eq!(ColorSupport::Basic.downsample(Color::AnsiValue(21)), Color::DarkBlue);
+        // named colors are shown everywhere
+        assert_eq!(ColorSupport::Basic.downsample(Color::Cyan), Color::Cyan);
+        assert_eq!(ColorSupport::Ansi256.downsample(Color::AnsiValue(3)), Color::AnsiValue(3));
+    }
+
+    #[test]
+    fn lighten_colors() {
+        assert_eq!(lighten(Color::Rgb { r: 255, g: 0, b: 100 }), Color::Rgb { r: 255, g: 127, b: 177 });
+        assert_eq!(lighten(Color::DarkBlue), Color::Rgb { r: 127, g: 127, b: 246 });
+        assert_eq!(lighten(Color::AnsiValue(232)), Color::Rgb { r: 131, g: 131, b: 131 });
+    }
+}
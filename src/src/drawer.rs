@@ -1,73 +1,33 @@
 use crate::tetris::{Point, Size};
-use std::io::{stdout};
+use crate::theme::Theme;
+use std::cell::RefCell;
+use std::io::{stdout, Stdout, Write};
 
 use crossterm::{
-    ExecutableCommand,
-    style::{Color, Print, ResetColor, SetForegroundColor},
-    terminal::{enable_raw_mode, Clear, ClearType, SetSize},
+    QueueableCommand,
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, Clear, ClearType, SetTitle},
     cursor::{MoveTo, Hide},
 };
 
 
 pub trait Drawer {
+    // start a screen of this size in the middle of the terminal, everything is
+    // drawn relative to it and nothing outside of it
     fn resize(&self, size: &Size);
+    fn terminal_size(&self) -> Size;
+    fn get_theme(&self) -> &Theme;
+    // the title of the terminal window, if it has one
+    fn set_title(&self, _title: &str) {}
+    // strings without a color have the text color of the theme
     fn draw_string(&self, x: u16, y: u16, content: &String, color: Option<Color>);
-    fn draw_string_on_point(&self, point: Point, content: &String, color: Option<Color>);
-    fn draw_region(&self, x: u16, y: u16, w: u16, h: u16, filler: &String);
-    fn draw_frame(&self, x: u16, y: u16, w: u16, h: u16);
-}
-
-pub struct NothingDrawer {}
-
-impl Drawer for NothingDrawer {
-    fn resize(&self, size: &Size) {}
-    fn draw_string(&self, x: u16, y: u16, content: &String, color: Option<Color>) {}
-    fn draw_string_on_point(&self, point: Point, content: &String, color: Option<Color>) {}
-    fn draw_region(&self, x: u16, y: u16, w: u16, h: u16, filler: &String) {}
-    fn draw_frame(&self, x: u16, y: u16, w: u16, h: u16) {}
-}
-
-impl NothingDrawer {
-    pub(crate) fn new() -> NothingDrawer {
-        NothingDrawer {}
-    }
-}
-
-pub struct CommandLineDrawer {}
+    // show everything drawn since the last flush
+    fn flush(&self);
 
-impl CommandLineDrawer {
-    pub fn new() -> CommandLineDrawer {
-        CommandLineDrawer {}
-    }
-}
-
-
-impl Drawer for CommandLineDrawer {
-    fn resize(&self, size: &Size) {
-        let mut stdout = stdout();
-        //going into raw mode
-        enable_raw_mode().unwrap();
-
-        stdout
-            .execute(Clear(ClearType::All)).unwrap()
-            .execute(SetSize(size.width, size.height)).unwrap()
-            .execute(Hide).unwrap();
-    }
-
-    fn draw_string(&self, x: u16, y: u16, content: &String, color: Option<Color>) {
-        let mut stdout = stdout();
-        stdout
-            .execute(MoveTo(x, y)).unwrap();
-
-        if color.is_none() {
-            stdout
-                .execute(Print(content)).unwrap();
-        } else {
-            stdout
-                .execute(SetForegroundColor(color.unwrap())).unwrap()
-                .execute(Print(content)).unwrap()
-                .execute(ResetColor).unwrap();
-        }
+    // on a background of its own instead of the one of the theme, drawers
+    // without backgrounds draw the string alone
+    fn draw_string_on_background(&self, x: u16, y: u16, content: &String, color: Option<Color>, _background: Color) {
+        self.draw_string(x, y, content, color);
     }
 
     fn draw_string_on_point(&self, point: Point, content: &String, color: Option<Color>) {
@@ -82,6 +42,7 @@ impl Drawer for CommandLineDrawer {
     }
 
     fn draw_frame(&self, x: u16, y: u16, w: u16, h: u16) {
+        let frame = self.get_theme().frame;
         let bottom = y + h;
         let right = x + w;
         let bottom_index = bottom - 1;
@@ -90,17 +51,17 @@ impl Drawer for CommandLineDrawer {
             for j in y..bottom {
                 let content =
                     if i == x && j == y {
-                        Some(String::from("┏"))
+                        Some(frame.top_left.to_string())
                     } else if i == right_index && j == y {
-                        Some(String::from("┓"))
+                        Some(frame.top_right.to_string())
                     } else if i == x && j == bottom_index {
-                        Some(String::from("┗"))
+                        Some(frame.bottom_left.to_string())
                     } else if i == right_index && j == bottom_index {
-                        Some(String::from("┛"))
+                        Some(frame.bottom_right.to_string())
                     } else if i == x || i == right_index {
-                        Some(String::from("┃"))
+                        Some(frame.vertical.to_string())
                     } else if j == y || j == bottom_index {
-                        Some(String::from("━"))
+                        Some(frame.horizontal.to_string())
                     } else {
                         None
                     };
@@ -110,4 +71,397 @@ impl Drawer for CommandLineDrawer {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+// a cell of the terminal with its color and background, the cell right of a
+// wide glyph is covered by it
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Cell {
+    Glyph(char, Option<Color>, Option<Color>),
+    Covered,
+}
+
+static BLANK: Cell = Cell::Glyph(' ', None, None);
+
+// width of ■ is 2, like the other wide glyphs of east asian scripts
+fn glyph_width(glyph: char) -> u16 {
+    match glyph as u32 {
+        0x25A0 | 0x1100..=0x115F | 0x2E80..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F | 0xFF00..=0xFF60 | 0xFFE0..=0xFFE6 | 0x1F300..=0x1FAFF => 2,
+        _ => 1,
+    }
+}
+
+// the columns a text takes on the terminal
+pub(crate) fn get_text_width(text: &str) -> u16 {
+    text.chars().map(glyph_width).sum()
+}
+
+// what is on the terminal and what is going to be, only the difference is written
+struct ScreenBuffer {
+    size: Size,
+    // strings are drawn in this region, relative to its corner
+    origin: Point,
+    region: Size,
+    background: Option<Color>,
+    front: Vec<Cell>,
+    back: Vec<Cell>,
+}
+
+impl ScreenBuffer {
+    fn new(size: Size) -> ScreenBuffer {
+        let cells = (size.width as usize) * (size.height as usize);
+        ScreenBuffer {
+            size,
+            origin: Point::new(0, 0),
+            region: size,
+            background: None,
+            front: vec![BLANK; cells],
+            back: vec![BLANK; cells],
+        }
+    }
+
+    // a blank terminal with the region in its middle
+    fn centered(terminal_size: Size, size: Size) -> ScreenBuffer {
+        let mut screen = ScreenBuffer::new(terminal_size);
+        if terminal_size.width >= size.width && terminal_size.height >= size.height {
+            let origin = Point::new((terminal_size.width - size.width) / 2, (terminal_size.height - size.height) / 2);
+            screen.set_region(origin, size);
+        } else {
+            // drawing in a smaller terminal would only wrap into garbage
+            let notice = format!("Terminal too small, {}x{} is needed", size.width, size.height);
+            screen.put_string(0, 0, &notice, Some(Color::Yellow));
+            screen.set_region(Point::new(0, 0), Size { width: 0, height: 0 });
+        }
+        screen
+    }
+
+    fn set_region(&mut self, origin: Point, region: Size) {
+        self.origin = origin;
+        self.region = region;
+    }
+
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        if x < self.size.width && y < self.size.height {
+            Some(y as usize * self.size.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+
+    // a glyph drawn over half of a wide one clears the other half
+    fn put(&mut self, x: u16, y: u16, glyph: char, color: Option<Color>, background: Option<Color>) {
+        let index = match self.index(x, y) {
+            Some(index) => index,
+            None => return,
+        };
+        self.clear_wide_glyph(x, y);
+        self.back[index] = Cell::Glyph(glyph, color, background);
+        if glyph_width(glyph) == 2 {
+            if let Some(covered) = self.index(x + 1, y) {
+                self.clear_wide_glyph(x + 1, y);
+                self.back[covered] = Cell::Covered;
+            }
+        }
+    }
+
+    fn clear_wide_glyph(&mut self, x: u16, y: u16) {
+        let index = self.index(x, y).unwrap();
+        match self.back[index] {
+            Cell::Covered => self.back[index - 1] = BLANK,
+            Cell::Glyph(glyph, _, _) if glyph_width(glyph) == 2 => {
+                if let Some(covered) = self.index(x + 1, y) {
+                    self.back[covered] = BLANK;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn put_string(&mut self, x: u16, y: u16, content: &str, color: Option<Color>) {
+        self.put_string_on(x, y, content, color, None);
+    }
+
+    // a background of None is the one of the screen
+    fn put_string_on(&mut self, mut x: u16, y: u16, content: &str, color: Option<Color>, background: Option<Color>) {
+        for glyph in content.chars() {
+            if x < self.region.width && y < self.region.height {
+                self.put(self.origin.x + x, self.origin.y + y, glyph, color, background);
+            }
+            x = x.saturating_add(glyph_width(glyph));
+        }
+    }
+
+    // write the changed cells and flush once
+    fn flush_to(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        // where the terminal cursor is, unknown after a wide glyph
+        let mut cursor = None;
+        let mut current_color = None;
+        let mut current_background = self.background;
+        if let Some(background) = self.background {
+            out.queue(SetBackgroundColor(background))?;
+        }
+        for y in 0..self.size.height {
+            for x in 0..self.size.width {
+                let index = self.index(x, y).unwrap();
+                let cell = self.back[index];
+                if cell == self.front[index] {
+                    continue;
+                }
+                self.front[index] = cell;
+                // the glyph on its left has changed as well and covers it
+                let (glyph, color, background) = match cell {
+                    Cell::Glyph(glyph, color, background) => (glyph, color, background.or(self.background)),
+                    Cell::Covered => continue,
+                };
+                if cursor != Some((x, y)) {
+                    out.queue(MoveTo(x, y))?;
+                }
+                if color != current_color {
+                    match color {
+                        Some(color) => out.queue(SetForegroundColor(color))?,
+                        None => out.queue(SetForegroundColor(Color::Reset))?,
+                    };
+                    current_color = color;
+                }
+                if background != current_background {
+                    out.queue(SetBackgroundColor(background.unwrap_or(Color::Reset)))?;
+                    current_background = background;
+                }
+                out.queue(Print(glyph))?;
+                cursor = if glyph_width(glyph) == 1 { Some((x + 1, y)) } else { None };
+            }
+        }
+        if current_color.is_some() || current_background.is_some() || self.background.is_some() {
+            out.queue(ResetColor)?;
+        }
+        out.flush()
+    }
+}
+
+// keeps what is drawn instead of showing it, to look at it in tests
+#[cfg(test)]
+pub(crate) struct CaptureDrawer {
+    terminal_size: Size,
+    theme: Theme,
+    screen: RefCell<ScreenBuffer>,
+}
+
+#[cfg(test)]
+impl CaptureDrawer {
+    pub(crate) fn new(terminal_size: Size) -> CaptureDrawer {
+        CaptureDrawer::with_theme(terminal_size, Theme::default())
+    }
+
+    pub(crate) fn with_theme(terminal_size: Size, theme: Theme) -> CaptureDrawer {
+        CaptureDrawer {
+            terminal_size,
+            theme,
+            screen: RefCell::new(ScreenBuffer::new(terminal_size)),
+        }
+    }
+
+    // the text of every row, the trailing blanks cut off
+    pub(crate) fn get_lines(&self) -> Vec<String> {
+        let screen = self.screen.borrow();
+        screen.back.chunks(screen.size.width.max(1) as usize)
+            .map(|row| {
+                let line: String = row.iter()
+                    .filter_map(|cell| match cell {
+                        Cell::Glyph(glyph, _, _) => Some(*glyph),
+                        Cell::Covered => None,
+                    })
+                    .collect();
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    pub(crate) fn get_color(&self, x: u16, y: u16) -> Option<Color> {
+        let screen = self.screen.borrow();
+        match screen.back[screen.index(x, y).unwrap()] {
+            Cell::Glyph(_, color, _) => color,
+            Cell::Covered => None,
+        }
+    }
+
+    pub(crate) fn get_background(&self, x: u16, y: u16) -> Option<Color> {
+        let screen = self.screen.borrow();
+        match screen.back[screen.index(x, y).unwrap()] {
+            Cell::Glyph(_, _, background) => background,
+            Cell::Covered => None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Drawer for CaptureDrawer {
+    fn resize(&self, size: &Size) {
+        *self.screen.borrow_mut() = ScreenBuffer::centered(self.terminal_size, *size);
+    }
+
+    fn terminal_size(&self) -> Size {
+        self.terminal_size
+    }
+
+    fn get_theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    fn draw_string(&self, x: u16, y: u16, content: &String, color: Option<Color>) {
+        self.screen.borrow_mut().put_string(x, y, content, color.or(self.theme.text_color));
+    }
+
+    fn draw_string_on_background(&self, x: u16, y: u16, content: &String, color: Option<Color>, background: Color) {
+        self.screen.borrow_mut().put_string_on(x, y, content, color.or(self.theme.text_color), Some(background));
+    }
+
+    fn flush(&self) {}
+}
+
+// draws on a terminal through a writer, the one of this process or the one at the other end of
+// a session of the server
+pub struct CommandLineDrawer<W: Write = Stdout> {
+    theme: Theme,
+    out: RefCell<W>,
+    terminal_size: Box<dyn Fn() -> Size>,
+    screen: RefCell<ScreenBuffer>,
+}
+
+impl CommandLineDrawer {
+    pub fn new(theme: Theme) -> CommandLineDrawer {
+        CommandLineDrawer::with_writer(theme, stdout(), || {
+            let (width, height) = terminal::size().unwrap_or((0, 0));
+            Size { width, height }
+        })
+    }
+}
+
+impl<W: Write> CommandLineDrawer<W> {
+    // the size of a terminal which is not the one of this process is told by the terminal itself
+    pub fn with_writer(theme: Theme, out: W, terminal_size: impl Fn() -> Size + 'static) -> CommandLineDrawer<W> {
+        CommandLineDrawer {
+            theme,
+            out: RefCell::new(out),
+            terminal_size: Box::new(terminal_size),
+            screen: RefCell::new(ScreenBuffer::new(Size { width: 0, height: 0 })),
+        }
+    }
+}
+
+impl<W: Write> Drawer for CommandLineDrawer<W> {
+    fn resize(&self, size: &Size) {
+        let mut out = self.out.borrow_mut();
+        if let Some(background) = self.theme.background_color {
+            let _ = out.queue(SetBackgroundColor(background));
+        }
+        let _ = out.queue(Clear(ClearType::All)).and_then(|out| out.queue(Hide)).and_then(|out| out.flush());
+        // the terminal is blank now
+        let mut screen = ScreenBuffer::centered(self.terminal_size(), *size);
+        screen.background = self.theme.background_color;
+        *self.screen.borrow_mut() = screen;
+    }
+
+    fn terminal_size(&self) -> Size {
+        (self.terminal_size)()
+    }
+
+    fn get_theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    fn set_title(&self, title: &str) {
+        let _ = self.out.borrow_mut().queue(SetTitle(title));
+    }
+
+    fn draw_string(&self, x: u16, y: u16, content: &String, color: Option<Color>) {
+        let color = color.or(self.theme.text_color);
+        self.screen.borrow_mut().put_string(x, y, content, color);
+    }
+
+    fn draw_string_on_background(&self, x: u16, y: u16, content: &String, color: Option<Color>, background: Color) {
+        let color = color.or(self.theme.text_color);
+        self.screen.borrow_mut().put_string_on(x, y, content, color, Some(background));
+    }
+
+    // a session whose terminal is gone ends with its input, not here
+    fn flush(&self) {
+        let _ = self.screen.borrow_mut().flush_to(&mut *self.out.borrow_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Color;
+    use crate::drawer::*;
+
+    fn flush(screen: &mut ScreenBuffer) -> String {
+        let mut out = Vec::new();
+        screen.flush_to(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn only_changes_are_written() {
+        let mut screen = ScreenBuffer::new(Size { width: 10, height: 3 });
+        screen.put_string(1, 0, "ab", None);
+        screen.put_string(2, 2, "c", Some(Color::Red));
+        assert_eq!(flush(&mut screen), "\x1b[1;2Hab\x1b[3;3H\x1b[38;5;9mc\x1b[0m");
+        assert_eq!(flush(&mut screen), "");
+
+        // drawing the same again is not written
+        screen.put_string(1, 0, "ax", None);
+        assert_eq!(flush(&mut screen), "\x1b[1;3Hx");
+        screen.put_string(20, 20, "out of the screen", None);
+        assert_eq!(flush(&mut screen), "");
+    }
+
+    #[test]
+    fn draw_in_region() {
+        let mut screen = ScreenBuffer::new(Size { width: 10, height: 5 });
+        screen.set_region(Point::new(3, 2), Size { width: 4, height: 2 });
+        screen.put_string(1, 1, "abcdef", None);
+        screen.put_string(0, 2, "below", None);
+        assert_eq!(flush(&mut screen), "\x1b[4;5Habc");
+    }
+
+    #[test]
+    fn wide_glyphs() {
+        let mut screen = ScreenBuffer::new(Size { width: 6, height: 1 });
+        screen.put_string(0, 0, "■■", None);
+        assert_eq!(flush(&mut screen), "\x1b[1;1H■\x1b[1;3H■");
+        screen.put_string(0, 0, "  ■", None);
+        assert_eq!(flush(&mut screen), "\x1b[1;1H  ");
+
+        // half of a wide glyph is cleared with the other half
+        screen.put_string(3, 0, "x", None);
+        assert_eq!(screen.back[2], BLANK);
+        assert_eq!(flush(&mut screen), "\x1b[1;3H x");
+    }
+
+    #[test]
+    fn backgrounds() {
+        let mut screen = ScreenBuffer::new(Size { width: 4, height: 1 });
+        screen.put_string_on(0, 0, "ab", Some(Color::Red), Some(Color::Blue));
+        screen.put_string(2, 0, "c", None);
+        assert_eq!(flush(&mut screen), "\x1b[1;1H\x1b[38;5;9m\x1b[48;5;12mab\x1b[39m\x1b[49mc");
+
+        // without a background of its own a cell has the one of the screen
+        screen.background = Some(Color::Black);
+        screen.put_string(0, 0, "d", Some(Color::Red));
+        assert_eq!(flush(&mut screen), "\x1b[48;5;0m\x1b[1;1H\x1b[38;5;9md\x1b[0m");
+    }
+
+    #[test]
+    fn too_small_terminal() {
+        let drawer = CaptureDrawer::new(Size { width: 40, height: 4 });
+        drawer.resize(&Size { width: 4, height: 2 });
+        drawer.draw_frame(0, 0, 4, 2);
+        assert_eq!(drawer.get_lines(), vec!["", "                  ┏━━┓", "                  ┗━━┛", ""]);
+
+        drawer.resize(&Size { width: 50, height: 2 });
+        drawer.draw_frame(0, 0, 50, 2);
+        assert_eq!(drawer.get_lines(), vec!["Terminal too small, 50x2 is needed", "", "", ""]);
+        assert_eq!(drawer.get_color(0, 0), Some(Color::Yellow));
+    }
+}
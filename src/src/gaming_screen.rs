@@ -1,5 +1,8 @@
-mod data;
-mod view;
+pub(crate) use tetris_engine::data;
+pub(crate) mod view;
+mod editor;
+mod playback;
+mod animations;
 
 use crate::tetris;
 use crate::drawer;
@@ -9,240 +12,619 @@ use crate::player;
 use drawer::{Drawer};
 use std::time::{Duration, Instant};
 use crossterm::{
-    event::{poll, read, Event},
+    event::{Event, KeyEvent, KeyEventKind},
+    style::Color,
 };
 use crate::drawer::CommandLineDrawer;
-use crate::gaming_screen::data::bricks::{Brick, BrickCollection};
-use crate::gaming_screen::data::game_panel::{GamePanel, MoveDownResult, PutNewOneResult};
-use crate::gaming_screen::view::blocks_view::{BlocksRenderView, BlocksView};
+use crate::gaming_screen::animations::{Animations, LINE_CLEAR_FRAMES};
+use crate::gaming_screen::data::BlocksData;
+use crate::gaming_screen::data::game_state::GameState;
+use crate::gaming_screen::data::puzzle::Puzzle;
+use crate::gaming_screen::data::replay::{GameInput, Replay};
+use crate::gaming_screen::data::save::{SavedGame, SAVE_FILE};
+use crate::gaming_screen::view::blocks_view::{BlocksView, StackVisibility};
 use crate::gaming_screen::view::game_view::GameView;
 use crate::gaming_screen::view::info_view::InfoView;
+use crate::high_scores::{HighScore, HighScores};
+use crate::input::{poll, read, Action, AutoRepeat};
+use crate::network::Spectators;
 use crate::player::{Player, SoundTypes};
 use crate::screens::NextScreen;
-use crate::tetris::{AppSettings, Point, Size};
+use crate::tetris::{AppSettings, GameMode, Point, Size};
+use tetris_engine::bot::{Bot, DEFAULT_WEIGHTS};
+use tetris_engine::game::{GameCore, MasterState, NextResult};
+use crate::gaming_screen::data::master::FRAMES_PER_SECOND;
 
+use std::fs;
 use std::fs::File;
 use std::io::BufReader;
 use rodio::{Decoder, OutputStream, OutputStreamHandle, source::Source};
 
-struct GamingScreenCore {
-    manager: GamePanel,
-    brick_collection: BrickCollection,
-    next_brick: Option<Brick>,
-}
+// frames simulated at once at most, after a stall the game goes on from where it was
+static MAX_CATCH_UP_FRAMES: u32 = 10;
+// frames between two inputs of the computer, so its moves can be followed
+pub static BOT_INPUT_FRAMES: u32 = 6;
 
-#[derive(Eq, PartialEq, Debug, Copy, Clone)]
-enum NextResult {
-    Success,
-    LineClear(u16, Brick),
-    NewBrickPutIn(Brick),
-    GameOver,
+pub struct GamingScreen<'a> {
+    pub settings: &'a AppSettings,
+    drawer: &'a dyn Drawer,
+    info_view: InfoView<'a>,
+    game_view: GameView<'a>,
+    core: GameCore,
+    mode: GameMode,
+    puzzle_index: usize,
+    editing: bool,
+    seed: u64,
+    // the game being played, if the mode can be replayed
+    recording: Option<Replay>,
+    animations: Animations,
+    // everyone watching with --watch, if the game is shared with --spectate
+    spectators: Option<Spectators>,
+    // the table of a server with the name of the player of the session
+    high_scores: Option<(HighScores, String)>,
+    // the computer playing the demo
+    bot: Option<Bot>,
 }
 
+impl GamingScreen<'_> {
+    pub fn new<'a>(settings: &'a AppSettings, drawer: &'a dyn Drawer) -> GamingScreen<'a> {
+        let game_view = GameView::new(Point::new(0, 0), settings.gaming_region, drawer);
+        let core = GameCore::new(settings.gaming_blocks_size);
+        let info_view = InfoView::new(&settings, drawer);
 
-impl GamingScreenCore {
-    fn next(&mut self) -> NextResult {
-        // move
-        let down = self.manager.move_down();
-        return match down {
-            MoveDownResult::NeedNewOne => {
-                let line_clear_result = self.manager.try_clean_lines();
-                let put_new_one_result = self.manager.put_new_one(&self.next_brick.unwrap());
-                return match put_new_one_result {
-                    PutNewOneResult::Success => {
-                        let new_brick = self.brick_collection.get_rand_one();
-                        self.next_brick = Some(new_brick);
-                        if line_clear_result.is_ok() {
-                            NextResult::LineClear(line_clear_result.unwrap().clear_lines.len() as u16, new_brick)
-                        } else {
-                            NextResult::NewBrickPutIn(new_brick)
-                        }
-                    }
-                    PutNewOneResult::GameOver => {
-                        return NextResult::GameOver;
-                    }
-                };
-            }
-            MoveDownResult::Success => {
-                NextResult::Success
-            }
+        GamingScreen {
+            settings,
+            drawer,
+            info_view,
+            game_view,
+            core,
+            mode: GameMode::Classic,
+            puzzle_index: 0,
+            editing: false,
+            seed: 0,
+            recording: None,
+            animations: Animations::new(settings.animations),
+            spectators: None,
+            high_scores: None,
+            bot: None,
+        }
+    }
+
+    pub fn share_with(&mut self, spectators: Spectators) {
+        self.spectators = Some(spectators);
+    }
+
+    pub fn share_scores(&mut self, high_scores: HighScores, name: String) {
+        self.high_scores = Some((high_scores, name));
+    }
+
+    pub fn new_game(&mut self, mode: GameMode) {
+        self.discard_saved_game();
+        self.start_game(mode, rand::random());
+        self.recording = if mode.is_recordable() && self.settings.saves {
+            Some(Replay::new(mode, self.seed))
+        } else {
+            None
         };
     }
 
-    fn rotate(&mut self) {
-        self.manager.rotate_current_brick()
+    fn start_game(&mut self, mode: GameMode, seed: u64) {
+        self.mode = mode;
+        self.seed = seed;
+        self.game_view.set_stack_visibility(self.get_stack_visibility());
+        self.game_view.set_tick_duration(mode.tick_duration());
+        self.game_view.set_block_scale(mode.block_scale());
+        let blocks_size = mode.get_blocks_size(self.settings.gaming_blocks_size);
+        self.core.start(mode, blocks_size, self.settings.master_timing, seed);
+        self.show_new_game();
+        // practice starts with an empty board to edit
+        self.editing = mode == GameMode::Practice;
+        self.bot = if mode == GameMode::Demo { Some(Bot::new(DEFAULT_WEIGHTS)) } else { None };
     }
 
-    fn move_left(&mut self) {
-        self.manager.move_current_brick_to_left()
+    // puzzles and practice are not saved, like they are not recorded, and neither are the games of a session
+    fn save_game(&self) {
+        if !self.mode.is_recordable() || !self.settings.saves {
+            return;
+        }
+        let (score, lines) = self.info_view.get_score();
+        let saved_game = SavedGame {
+            mode: self.mode,
+            seed: self.seed,
+            draws: self.core.brick_collection.get_draws(),
+            score: score as u32,
+            lines,
+            ticks: self.core.manager.blocks.tick,
+            panel: self.core.manager.snapshot(),
+            next_brick: self.core.next_brick,
+            hold: self.core.hold,
+            can_hold: self.core.can_hold,
+            master: self.core.master.as_ref().map(|state| state.progress.clone()),
+            master_timers: self.core.master.as_ref().map(MasterState::get_timers),
+            replay: self.recording.clone(),
+        };
+        let _ = fs::write(SAVE_FILE, saved_game.to_text());
     }
 
-    fn move_right(&mut self) {
-        self.manager.move_current_brick_to_right();
+    // the saved game stays until it ends, so it can be continued again after a crash
+    pub fn resume_game(&mut self) -> Result<(), String> {
+        let content = fs::read_to_string(SAVE_FILE).map_err(|e| e.to_string())?;
+        let saved_game = SavedGame::parse(&content)?;
+        let blocks_size = saved_game.mode.get_blocks_size(self.settings.gaming_blocks_size);
+        if saved_game.panel.blocks.get_size() != blocks_size {
+            return Err(String::from("the saved board does not fit the game panel"));
+        }
+        self.start_game(saved_game.mode, saved_game.seed);
+        self.core.brick_collection.skip(saved_game.draws);
+        // the stack counts as locked on the saved tick
+        self.core.manager.blocks.tick = saved_game.ticks;
+        self.core.manager.restore(&saved_game.panel);
+        self.core.next_brick = saved_game.next_brick;
+        self.core.hold = saved_game.hold;
+        self.core.can_hold = saved_game.can_hold;
+        if let Some(state) = self.core.master.as_mut() {
+            state.progress = saved_game.master.ok_or("missing master progress")?;
+            state.set_timers(saved_game.master_timers.ok_or("missing master timers")?);
+            self.info_view.update_master_info(state.progress.get_info());
+        }
+        self.info_view.set_score(saved_game.score as f64, saved_game.lines);
+        self.recording = saved_game.replay;
+        self.render_game();
+        self.update_bricks();
+        Ok(())
     }
 
-    fn move_bottom(&mut self) {
-        self.manager.move_current_brick_to_bottom()
+    // the recording of a game which is not continued is kept as a replay
+    fn discard_saved_game(&mut self) {
+        if !self.settings.saves {
+            return;
+        }
+        if let Ok(content) = fs::read_to_string(SAVE_FILE) {
+            if let Ok(SavedGame { replay: Some(replay), .. }) = SavedGame::parse(&content) {
+                if replay.ticks > 0 {
+                    let _ = replay.save();
+                }
+            }
+            let _ = fs::remove_file(SAVE_FILE);
+        }
     }
 
-    fn render_to(&self, component: &dyn BlocksRenderView) {
-        component.render_blocks(&self.manager.blocks)
+    pub fn new_puzzle(&mut self, index: usize, puzzle: &Puzzle) -> Result<(), String> {
+        self.mode = GameMode::Puzzle;
+        self.puzzle_index = index;
+        self.game_view.set_stack_visibility(self.get_stack_visibility());
+        self.game_view.set_block_scale(1);
+        self.core.start_puzzle(puzzle, self.settings.gaming_blocks_size)?;
+        self.render_game();
+        self.info_view.reset();
+        self.update_bricks();
+        self.update_puzzle_info();
+        Ok(())
     }
 
-    fn reset(&mut self) {
-        self.manager.reset();
-        let brick = self.brick_collection.get_rand_one();
-        self.next_brick = Some(brick);
+    fn update_puzzle_info(&mut self) {
+        if let Some(puzzle) = &self.core.puzzle {
+            let pieces_left = puzzle.pieces.len() + self.core.next_brick.iter().count();
+            self.info_view.update_puzzle_info(puzzle.goal.describe(), pieces_left);
+        }
+        if let Some(practice) = &self.core.practice {
+            self.info_view.update_practice_info(practice.get_queue_text(), self.editing);
+        }
     }
-}
 
-pub struct GamingScreen<'a> {
-    pub settings: &'a AppSettings,
-    drawer: &'a dyn Drawer,
-    info_view: InfoView<'a>,
-    game_view: GameView<'a>,
-    core: GamingScreenCore,
-}
+    fn undo(&mut self, redo: bool) {
+        let changed = if redo { self.core.redo() } else { self.core.undo() };
+        if changed {
+            self.render_game();
+            self.update_bricks();
+            self.update_puzzle_info();
+            self.info_view.render_data();
+        }
+    }
 
-impl GamingScreen<'_> {
-    pub fn new<'a>(settings: &'a AppSettings, drawer: &'a dyn Drawer) -> GamingScreen<'a> {
-        let game_view = GameView::new(settings.gaming_region, drawer);
-        let mut game_panel = GamePanel::new(settings.gaming_blocks_size, &settings);
-        let collection = data::bricks::BrickCollection::new();
-        let mut core = GamingScreenCore {
-            manager: game_panel,
-            brick_collection: collection,
-            next_brick: None,
-        };
+    fn reset_practice(&mut self) {
+        self.core.reset_practice();
+        self.render_game();
+        self.update_bricks();
+        self.update_puzzle_info();
+        self.info_view.render_data();
+    }
+
+    fn get_stack_visibility(&self) -> StackVisibility {
+        match self.mode {
+            GameMode::Classic | GameMode::Big | GameMode::Master | GameMode::Puzzle | GameMode::Practice | GameMode::Versus
+            | GameMode::Demo => {
+                StackVisibility::Visible
+            }
+            GameMode::Invisible => StackVisibility::Invisible,
+            GameMode::Fading => StackVisibility::Fading,
+        }
+    }
 
-        core.reset();
+    // show the whole stack until any key is pressed
+    fn reveal_stack(&mut self) -> std::io::Result<()> {
+        self.game_view.set_stack_visibility(StackVisibility::Visible);
+        self.render_game();
+        self.wait_for_any_key("GAME OVER")?;
+        self.game_view.set_stack_visibility(self.get_stack_visibility());
+        Ok(())
+    }
 
-        // test code
-        // let size = Size {
-        //     height: 4,
-        //     width: settings.gaming_blocks_size.width,
-        // };
-        // let point = Point::new(0, settings.gaming_blocks_size.height - 4);
-        // core.manager.set_region(point, size, true);
+    // the best games of the mode with the one just over among them, until any key is pressed
+    fn show_high_scores(&mut self) -> std::io::Result<()> {
+        let (high_scores, name) = match self.high_scores.clone() {
+            Some(high_scores) => high_scores,
+            None => return Ok(()),
+        };
+        let (score, lines) = self.info_view.get_score();
+        let place = high_scores.add(HighScore { name, mode: self.mode, score: score as u32, lines });
+        let entries = high_scores.get_entries(self.mode);
+        let region = self.settings.gaming_region;
+        self.wait_showing(|drawer| {
+            drawer.draw_region(1, 1, region.width - 2, region.height - 2, &String::from(" "));
+            let title = String::from("HIGH SCORES");
+            drawer.draw_string((region.width - title.len() as u16) / 2, 2, &title, None);
+            for (index, entry) in entries.iter().enumerate() {
+                let name: String = entry.name.chars().take(10).collect();
+                let text = format!("{:>2} {:<10} {:>7}", index + 1, name, entry.score);
+                let color = if place == Some(index) { Some(Color::Yellow) } else { None };
+                drawer.draw_string(1, 4 + index as u16, &text, color);
+            }
+        })
+    }
 
-        let info_view = InfoView::new(&settings, drawer);
+    // show a message over the game view until any key is pressed
+    fn wait_for_any_key(&mut self, message: &str) -> std::io::Result<()> {
+        let region = self.settings.gaming_region;
+        let text = format!(" {} ", message);
+        self.wait_showing(|drawer| drawer.draw_string((region.width - text.len() as u16) / 2, region.height / 2, &text, None))
+    }
 
-        GamingScreen {
-            settings,
-            drawer,
-            info_view,
-            game_view,
-            core,
+    // draw over the game view until any key is pressed
+    fn wait_showing(&mut self, draw: impl Fn(&dyn Drawer)) -> std::io::Result<()> {
+        loop {
+            draw(self.drawer);
+            self.drawer.flush();
+            if poll(Duration::from_millis(500))? {
+                match read()? {
+                    // a key released from the game does not count
+                    Event::Key(KeyEvent { kind: KeyEventKind::Press, .. }) => return Ok(()),
+                    Event::Resize(_, _) => {
+                        self.init();
+                        self.render();
+                    }
+                    _ => {}
+                }
+            }
         }
     }
 
-    fn init(&self) {
+    fn init(&mut self) {
         let drawer = self.drawer;
-        let window_size = self.settings.total_region;
+        let (window_size, info_point) = get_layout(self.settings, drawer.terminal_size());
         drawer.resize(&window_size);
-        drawer.draw_region(0, 0, window_size.width, window_size.width, &String::from(" "));
-        drawer.draw_frame(0, 0, window_size.width, window_size.width);
+        drawer.draw_region(0, 0, window_size.width, window_size.height, &String::from(" "));
 
+        self.info_view.move_to(info_point);
         self.info_view.init();
         self.game_view.init();
     }
 
-    fn move_right(&mut self) {
-        self.core.move_right();
-        self.core.render_to(&self.game_view);
+    fn render_game(&self) {
+        self.animations.render(&self.game_view, &self.core.manager.blocks);
+        if let Some(versus) = &self.core.versus {
+            self.game_view.render_garbage_meter(versus.get_pending_rows());
+        }
     }
 
-    fn move_left(&mut self) {
-        self.core.move_left();
-        self.core.render_to(&self.game_view);
+    fn get_falling_cells(&self) -> Vec<Point> {
+        match self.core.manager.current_brick {
+            Some(live_brick) => live_brick.brick.project_to_new_position(live_brick.position).points.to_vec(),
+            None => Vec::new(),
+        }
     }
 
-    fn move_bottom(&mut self) {
-        self.core.move_bottom();
-        self.core.render_to(&self.game_view);
+    fn update_bricks(&mut self) {
+        self.info_view.update_next_brick(self.core.next_brick);
+        self.info_view.update_hold_brick(self.core.hold);
     }
 
-    fn rotate(&mut self) {
-        self.core.rotate();
-        self.core.render_to(&self.game_view);
+    fn apply_input(&mut self, input: GameInput) {
+        self.core.apply_input(input);
+        if input == GameInput::Hold {
+            self.update_bricks();
+            self.update_puzzle_info();
+        }
     }
 
-    fn time_tick(&mut self) -> NextResult {
-        let result = self.core.next();
-        match result {
-            NextResult::Success => {
-                self.core.render_to(&self.game_view);
-            }
-            NextResult::GameOver => {}
-            NextResult::LineClear(count, next_brick) => {
-                self.core.render_to(&self.game_view);
-                self.info_view.add_lines(count);
-                self.info_view.update_next_brick(next_brick);
-                self.info_view.render_data();
+    // inputs which do not change the game are not recorded, like moves against a wall
+    fn play_input(&mut self, input: GameInput, player: &mut Player) {
+        let before = (self.core.manager.current_brick, self.core.hold, self.core.can_hold);
+        let falling_cells = self.get_falling_cells();
+        self.apply_input(input);
+        if (self.core.manager.current_brick, self.core.hold, self.core.can_hold) == before {
+            return;
+        }
+        if input == GameInput::Drop {
+            self.add_drop_trail(falling_cells);
+        }
+        if let Some(recording) = self.recording.as_mut() {
+            recording.push(input);
+        }
+        if input.is_rotation() {
+            player.play(SoundTypes::Change);
+        }
+    }
+
+    // the cells between where the brick was and where it landed
+    fn add_drop_trail(&mut self, falling_cells: Vec<Point>) {
+        let distance = match (falling_cells.first(), self.get_falling_cells().first()) {
+            (Some(before), Some(after)) => after.y - before.y,
+            _ => return,
+        };
+        let trail = falling_cells.iter()
+            .flat_map(|cell| (cell.y..cell.y + distance).map(move |y| Point::new(cell.x, y)))
+            .collect();
+        self.animations.add_trail(trail);
+    }
+
+    // the effects of a tick, returns the frames the game waits for them
+    fn animate_tick(&mut self, result: NextResult, falling_cells: Vec<Point>, board: Option<BlocksData>) -> u32 {
+        if let (NextResult::LineClear(_, _), Some(board)) = (result, board) {
+            return match &self.core.master {
+                // master mode waits for its own line clear delay
+                Some(state) => {
+                    self.animations.start_line_clear(board, state.timing.line_clear_delay as u32);
+                    0
+                }
+                None => self.animations.start_line_clear(board, LINE_CLEAR_FRAMES),
+            };
+        }
+        // the brick locked, a new one may have been put in already
+        let locked = match result {
+            NextResult::NewBrickPutIn(_) => true,
+            NextResult::Success | NextResult::Waiting => self.core.manager.current_brick.is_none(),
+            _ => false,
+        };
+        if locked {
+            self.animations.flash_lock(falling_cells);
+        }
+        0
+    }
+
+    // keep the finished game and go on recording the next one
+    fn save_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            if recording.ticks > 0 {
+                let _ = recording.save();
             }
-            NextResult::NewBrickPutIn(next_brick) => {
-                self.core.render_to(&self.game_view);
-                self.info_view.update_next_brick(next_brick);
-                self.info_view.render_data();
+        }
+    }
+
+    // a tick of the game, it is drawn by the next render
+    fn time_tick(&mut self) -> NextResult {
+        let result = self.core.next_frame();
+        self.update_info(result);
+        if let NextResult::LineClear(_, _) | NextResult::NewBrickPutIn(_) = result {
+            self.update_bricks();
+            self.update_puzzle_info();
+            if let Some(bot) = self.bot.as_mut() {
+                bot.on_new_brick(&self.core);
             }
-        };
+        }
         result
     }
 
+    fn render(&self) {
+        self.render_game();
+        self.info_view.render_data();
+        if let Some(spectators) = &self.spectators {
+            let (score, lines) = self.info_view.get_score();
+            spectators.broadcast(self.mode, &GameState::new(&self.core, score as u32, lines));
+            // how many are watching goes on the bottom of the game view
+            let watching_text = format!(" {:<3} watching ", spectators.count());
+            self.drawer.draw_string(2, self.settings.gaming_region.height - 1, &watching_text, None);
+        }
+        self.drawer.flush();
+    }
+
+    // count the lines of a tick without drawing anything
+    fn update_info(&mut self, result: NextResult) {
+        if let Some(state) = &self.core.master {
+            self.info_view.update_master_info(state.progress.get_info());
+        } else if let NextResult::LineClear(count, _) = result {
+            self.info_view.add_lines(count);
+        }
+    }
+
     fn reset(&mut self) {
         self.core.reset();
-        self.core.render_to(&self.game_view);
-        let brick = &self.core.next_brick.unwrap();
+        self.show_new_game();
+    }
+
+    fn show_new_game(&mut self) {
+        self.animations.clear();
+        self.render_game();
         self.info_view.reset();
-        self.info_view.update_next_brick(brick.clone());
+        self.update_bricks();
+        if let Some(state) = &self.core.master {
+            self.info_view.update_master_info(state.progress.get_info());
+        }
+        self.update_puzzle_info();
+    }
+}
+
+// the info view goes beside the game view, or below it if the terminal is too narrow, returns
+// the size of the window and where the info view starts
+pub(crate) fn get_layout(settings: &AppSettings, terminal_size: Size) -> (Size, Point) {
+    let gaming_region = settings.gaming_region;
+    let info_region = settings.info_region;
+    let below = terminal_size.width < gaming_region.width + info_region.width
+        && terminal_size.height >= gaming_region.height + info_region.height;
+    if below {
+        let window_size = Size {
+            width: gaming_region.width.max(info_region.width),
+            height: gaming_region.height + info_region.height,
+        };
+        (window_size, Point::new((window_size.width - info_region.width) / 2, gaming_region.height))
+    } else {
+        let window_size = Size {
+            width: gaming_region.width + info_region.width,
+            height: gaming_region.height.max(info_region.height),
+        };
+        (window_size, Point::new(gaming_region.width, 0))
     }
 }
 
 impl screens::LoadScreen for GamingScreen<'_> {
-    fn load(&mut self) -> NextScreen {
+    fn load(&mut self) -> std::io::Result<NextScreen> {
+        self.animations.clear();
         self.init();
-        let mut player = Player::new();
+        self.render();
+        let mut player = if self.settings.sounds { Player::new() } else { Player::silent() };
         player.play_repeat(SoundTypes::Gaming);
 
-        let mut last_tick_time = Instant::now();
-        let mut last_action_time = Instant::now();
-        let next_screen = loop {
-            let now = Instant::now();
-            if poll(Duration::from_millis(100)).unwrap() {
-                if now - last_action_time > Duration::from_millis(100) {
-                    last_action_time = now;
-                    match read().unwrap() {
-                        Event::Key(event) => {
-                            if event.code == self.settings.keyboard_control.exit {
-                                break NextScreen::Welcome;
-                            }
-                            if event.code == self.settings.keyboard_control.pause {
-                                break NextScreen::Pause;
-                            }
-                            if event.code == self.settings.keyboard_control.right {
-                                self.move_right();
+        if self.editing {
+            if let Some(next_screen) = self.edit_board()? {
+                return Ok(next_screen);
+            }
+        }
+        let settings = self.settings;
+        let frame_duration = Duration::from_secs(1) / FRAMES_PER_SECOND;
+        let frames_per_tick = self.mode.frames_per_tick();
+        // master mode soft drops one row per frame
+        let soft_drop_interval = if self.mode == GameMode::Master {
+            1
+        } else {
+            frames_per_tick / settings.handling.soft_drop_factor
+        };
+        let width = self.core.manager.blocks.get_size().width;
+        let mut auto_repeat = AutoRepeat::new(settings.handling, settings.key_releases, soft_drop_interval, width);
+        let mut frame: u32 = 0;
+        let mut next_tick_frame = frames_per_tick;
+        let mut next_frame_time = Instant::now() + frame_duration;
+        // the game waits until this frame while cleared lines are shown
+        let mut resume_frame = 0;
+        // events read while waiting for a frame, handled at its start
+        let mut events = Vec::new();
+        let next_screen = 'game: loop {
+            while poll(next_frame_time.saturating_duration_since(Instant::now()))? {
+                events.push(read()?);
+            }
+            // catch up on every frame that is due, but never spin after a long stall
+            if Instant::now().saturating_duration_since(next_frame_time) > frame_duration * MAX_CATCH_UP_FRAMES {
+                next_frame_time = Instant::now();
+            }
+            let mut changed = false;
+            while next_frame_time <= Instant::now() {
+                next_frame_time += frame_duration;
+                frame += 1;
+                changed |= self.animations.set_frame(frame);
+                // the events wait with the game
+                if frame < resume_frame {
+                    continue;
+                }
+                changed |= !events.is_empty();
+                for event in events.drain(..) {
+                    if let Event::Resize(_, _) = event {
+                        self.init();
+                    }
+                    for action in settings.key_bindings.get_releases(&event) {
+                        auto_repeat.release(action);
+                    }
+                    for action in settings.key_bindings.get_presses(&event) {
+                        // the demo only listens to the keys which leave it
+                        match action.get_game_input() {
+                            Some(input) if self.bot.is_none() && auto_repeat.press(action, frame) => {
+                                self.play_input(input, &mut player);
                             }
-                            if event.code == self.settings.keyboard_control.left {
-                                self.move_left();
+                            _ => {}
+                        }
+                        match action {
+                            Action::Exit => {
+                                self.save_game();
+                                break 'game NextScreen::Welcome;
                             }
-                            if event.code == self.settings.keyboard_control.down {
-                                self.move_bottom();
+                            Action::Pause => {
+                                self.save_game();
+                                break 'game NextScreen::Pause;
                             }
-                            if event.code == self.settings.keyboard_control.change {
-                                self.rotate();
-                                player.play(SoundTypes::Change);
+                            Action::Edit if self.core.practice.is_some() => {
+                                if let Some(next_screen) = self.edit_board()? {
+                                    break 'game next_screen;
+                                }
+                                auto_repeat.clear();
+                                next_tick_frame = frame + frames_per_tick;
+                                next_frame_time = Instant::now() + frame_duration;
                             }
+                            Action::Undo if self.core.practice.is_some() => self.undo(false),
+                            Action::Redo if self.core.practice.is_some() => self.undo(true),
+                            Action::Reset if self.core.practice.is_some() => self.reset_practice(),
+                            _ => {}
                         }
-                        _ => {}
                     }
                 }
-            }
-            if now - last_tick_time > Duration::from_millis(500) {
-                last_tick_time = now;
+                for input in auto_repeat.take_due(frame) {
+                    self.play_input(input, &mut player);
+                    changed = true;
+                }
+                if frame % BOT_INPUT_FRAMES == 0 {
+                    if let Some(input) = self.bot.as_mut().and_then(Bot::next_input) {
+                        self.play_input(input, &mut player);
+                        changed = true;
+                    }
+                }
+                if frame < next_tick_frame {
+                    continue;
+                }
+                next_tick_frame += frames_per_tick;
+                changed = true;
+                let falling_cells = self.get_falling_cells();
+                let board = if self.animations.is_enabled() { Some(self.core.manager.blocks.clone()) } else { None };
                 let next_result = self.time_tick();
+                let wait = self.animate_tick(next_result, falling_cells, board);
+                resume_frame = frame + wait;
+                next_tick_frame += wait;
+                if let Some(recording) = self.recording.as_mut() {
+                    recording.ticks += 1;
+                }
                 match next_result {
-                    NextResult::GameOver => {
+                    NextResult::PuzzleSolved => {
+                        self.render();
+                        self.wait_for_any_key("SOLVED!")?;
+                        break 'game NextScreen::PuzzleSolved(self.puzzle_index);
+                    }
+                    NextResult::GameOver if self.mode == GameMode::Puzzle => {
+                        self.render();
+                        self.wait_for_any_key("FAILED")?;
+                        break 'game NextScreen::PuzzleSelect;
+                    }
+                    NextResult::GameOver if self.mode == GameMode::Practice => {
+                        self.reset_practice();
+                    }
+                    // the demo starts over by itself
+                    NextResult::GameOver if self.mode == GameMode::Demo => {
                         self.reset();
+                        if let Some(bot) = self.bot.as_mut() {
+                            bot.clear();
+                        }
+                        next_tick_frame = frame + frames_per_tick;
+                    }
+                    NextResult::GameOver => {
+                        if self.mode.hides_stack() || self.mode == GameMode::Master {
+                            self.reveal_stack()?;
+                        }
+                        self.show_high_scores()?;
+                        if self.recording.is_some() {
+                            self.save_recording();
+                            let _ = fs::remove_file(SAVE_FILE);
+                            self.new_game(self.mode);
+                        } else {
+                            self.reset();
+                        }
+                        auto_repeat.clear();
+                        next_tick_frame = frame + frames_per_tick;
+                        next_frame_time = Instant::now() + frame_duration;
                     }
                     NextResult::LineClear(count, _) => {
                         player.play(SoundTypes::LineClean(count));
@@ -250,83 +632,33 @@ impl screens::LoadScreen for GamingScreen<'_> {
                     _ => {}
                 }
             }
+            // drawn once for all the frames simulated since the last time
+            if changed {
+                self.render();
+            }
         };
-        next_screen
+        Ok(next_screen)
     }
 }
 
-
 #[cfg(test)]
 mod tests {
-    use crossterm::event::KeyCode;
-    use crate::{drawer, tetris};
-    use crate::drawer::{Drawer, NothingDrawer};
-    use crate::gaming_screen::data::bricks::*;
-    use crate::gaming_screen::data::game_panel::{GamePanel, LiveBrick, MoveDownResult, PutNewOneResult};
-    use crate::gaming_screen::{data, GamingScreenCore, NextResult, view};
-    use crate::tetris::*;
-
-    static TEST_SIZE: Size = Size {
-        width: 10,
-        height: 20,
-    };
-
-
-    static TEST_APP_SETTINGS: AppSettings = AppSettings {
-        gaming_region: Size {
-            height: 20,
-            width: 10,
-        },
-        info_region: Size {
-            height: 20,
-            width: 20,
-        },
-        total_region: Size {
-            height: 20,
-            width: 30,
-        },
-        welcome_region: Size {
-            height: 22,
-            width: 52,
-        },
-        gaming_blocks_size: TEST_SIZE,
-        keyboard_control: KeyboardControl {
-            exit: KeyCode::Esc,
-            change: KeyCode::Up,
-            down: KeyCode::Down,
-            pause: KeyCode::Char(' '),
-            start: KeyCode::Enter,
-            left: KeyCode::Left,
-            right: KeyCode::Right,
-        },
-    };
+    use std::fs;
+    use crate::drawer::CaptureDrawer;
+    use crate::gaming_screen::*;
+    use crate::tetris::AppSettings;
 
     #[test]
-    fn next() {
-        let drawer = NothingDrawer::new();
-        let component = view::info_view::InfoView::new(&TEST_APP_SETTINGS, &drawer);
-        component.init();
-
-        let game_panel = view::game_view::GameView::new(TEST_APP_SETTINGS.gaming_region, &drawer);
-        game_panel.init();
-
-        let collection = data::bricks::BrickCollection::new();
-        let next_brick = collection.get_rand_one();
-        let mut core = GamingScreenCore {
-            manager: GamePanel::new(TEST_APP_SETTINGS.gaming_blocks_size, &TEST_APP_SETTINGS),
-            brick_collection: collection,
-            next_brick: Some(next_brick),
-        };
-
-        let result = core.next();
-        match result {
-            NextResult::NewBrickPutIn(_) => { assert!(true) }
-            _ => { assert!(false) }
-        }
-
-        let panel = &core.manager;
-        let brick = &panel.current_brick.unwrap();
-        assert_eq!(brick.position.y, 0);
-        assert_ne!(brick.position.x, 0);
+    fn sessions_do_not_save() {
+        let settings = AppSettings { saves: false, ..AppSettings::default() };
+        let drawer = CaptureDrawer::new(Size { width: 60, height: 24 });
+        let saved_before = fs::read_to_string(SAVE_FILE).ok();
+        let mut screen = GamingScreen::new(&settings, &drawer);
+        screen.new_game(GameMode::Classic);
+        assert!(screen.recording.is_none());
+        screen.time_tick();
+        screen.save_game();
+        // the saved game of the server is neither replaced nor removed
+        assert_eq!(fs::read_to_string(SAVE_FILE).ok(), saved_before);
     }
 }
@@ -0,0 +1,206 @@
+use crossterm::style::Color;
+use crate::gaming_screen::data::BlocksData;
+use crate::gaming_screen::view::blocks_view::BlocksRenderView;
+use crate::gaming_screen::view::game_view::GameView;
+use crate::tetris::Point;
+
+// how long each effect lasts, in frames
+pub(crate) static LINE_CLEAR_FRAMES: u32 = 20;
+static LOCK_FLASH_FRAMES: u32 = 6;
+static TRAIL_FRAMES: u32 = 8;
+
+// the full rows turn white and are wiped out from the middle
+struct LineClear {
+    // the board before the rows were cleared
+    board: BlocksData,
+    rows: Vec<usize>,
+    start: u32,
+    frames: u32,
+}
+
+// cells drawn in a color until a frame, as long as they are filled or empty like when it started
+struct Highlight {
+    cells: Vec<Point>,
+    filled: bool,
+    color: Color,
+    end: u32,
+}
+
+// effects drawn over the board, they never change the game
+pub(crate) struct Animations {
+    enabled: bool,
+    frame: u32,
+    line_clear: Option<LineClear>,
+    highlights: Vec<Highlight>,
+}
+
+impl Animations {
+    pub(crate) fn new(enabled: bool) -> Animations {
+        Animations {
+            enabled,
+            frame: 0,
+            line_clear: None,
+            highlights: Vec::new(),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.line_clear = None;
+        self.highlights.clear();
+    }
+
+    // drop the finished effects, returns whether the board has to be drawn again
+    pub(crate) fn set_frame(&mut self, frame: u32) -> bool {
+        let was_active = self.is_active();
+        self.frame = frame;
+        if self.line_clear.as_ref().is_some_and(|line_clear| frame >= line_clear.start + line_clear.frames) {
+            self.line_clear = None;
+        }
+        self.highlights.retain(|highlight| frame < highlight.end);
+        was_active || self.is_active()
+    }
+
+    fn is_active(&self) -> bool {
+        self.line_clear.is_some() || !self.highlights.is_empty()
+    }
+
+    // show the full rows of the board from before they were cleared, returns the
+    // frames it lasts, none if animations are off
+    pub(crate) fn start_line_clear(&mut self, board: BlocksData, frames: u32) -> u32 {
+        let rows: Vec<usize> = (0..board.blocks.len())
+            .filter(|y| board.blocks[*y].iter().all(|filled| *filled))
+            .collect();
+        if !self.enabled || rows.is_empty() || frames == 0 {
+            return 0;
+        }
+        self.line_clear = Some(LineClear {
+            board,
+            rows,
+            start: self.frame,
+            frames,
+        });
+        frames
+    }
+
+    pub(crate) fn flash_lock(&mut self, cells: Vec<Point>) {
+        self.highlight(cells, true, Color::White, LOCK_FLASH_FRAMES);
+    }
+
+    // the empty cells a hard dropped brick went through
+    pub(crate) fn add_trail(&mut self, cells: Vec<Point>) {
+        self.highlight(cells, false, Color::DarkGrey, TRAIL_FRAMES);
+    }
+
+    fn highlight(&mut self, cells: Vec<Point>, filled: bool, color: Color, frames: u32) {
+        if self.enabled && !cells.is_empty() {
+            self.highlights.push(Highlight { cells, filled, color, end: self.frame + frames });
+        }
+    }
+
+    pub(crate) fn render(&self, view: &GameView, board: &BlocksData) {
+        let line_clear = match &self.line_clear {
+            Some(line_clear) => line_clear,
+            None => {
+                view.render_blocks(board);
+                self.render_highlights(view, board);
+                return;
+            }
+        };
+        view.render_blocks(&line_clear.board);
+        let width = line_clear.board.get_size().width;
+        let half = width.div_ceil(2);
+        let elapsed = self.frame.saturating_sub(line_clear.start) + 1;
+        let wiped = (elapsed * half as u32 / line_clear.frames) as u16;
+        for y in line_clear.rows.iter() {
+            for x in 0..width {
+                let block = Point::new(x, *y as u16);
+                // how far the column is from the nearest wall
+                if x.min(width - 1 - x) + wiped >= half {
+                    view.render_empty_at(block);
+                } else {
+                    view.render_block_at(block, Color::White);
+                }
+            }
+        }
+    }
+
+    fn render_highlights(&self, view: &GameView, board: &BlocksData) {
+        for highlight in self.highlights.iter() {
+            for cell in highlight.cells.iter() {
+                let filled = board.blocks.get(cell.y as usize).and_then(|line| line.get(cell.x as usize));
+                if filled == Some(&highlight.filled) {
+                    view.render_block_at(*cell, highlight.color);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Color;
+    use crate::drawer::{CaptureDrawer, Drawer};
+    use crate::gaming_screen::animations::*;
+    use crate::gaming_screen::view::game_view::GameView;
+    use crate::tetris::{Point, Size};
+
+    fn render(animations: &Animations, board: &BlocksData) -> CaptureDrawer {
+        let size = Size { width: 12, height: 4 };
+        let drawer = CaptureDrawer::new(size);
+        drawer.resize(&size);
+        animations.render(&GameView::new(Point::new(0, 0), size, &drawer), board);
+        drawer
+    }
+
+    #[test]
+    fn line_clear() {
+        let mut animations = Animations::new(true);
+        let mut board = BlocksData::new(Size { width: 4, height: 2 });
+        board.set_region(Point::new(0, 0), Size { width: 1, height: 1 }, true);
+        board.set_region(Point::new(0, 1), Size { width: 4, height: 1 }, true);
+        let cleared = BlocksData::new(Size { width: 4, height: 2 });
+        assert_eq!(animations.start_line_clear(board, 4), 4);
+        assert!(animations.set_frame(1));
+        assert_eq!(render(&animations, &cleared).get_lines(), vec!["", "  ■", "  ■    ■", ""]);
+        animations.set_frame(3);
+        assert_eq!(render(&animations, &cleared).get_lines(), vec!["", "  ■", "", ""]);
+
+        // the board is drawn once more when it is over
+        assert!(animations.set_frame(4));
+        assert_eq!(render(&animations, &cleared).get_lines(), vec!["", "", "", ""]);
+        assert!(!animations.set_frame(5));
+    }
+
+    #[test]
+    fn highlights() {
+        let mut animations = Animations::new(true);
+        let mut board = BlocksData::new(Size { width: 4, height: 2 });
+        board.set_region(Point::new(0, 1), Size { width: 2, height: 1 }, true);
+        animations.flash_lock(vec![Point::new(0, 1), Point::new(1, 1)]);
+        animations.add_trail(vec![Point::new(0, 0), Point::new(0, 1)]);
+        let drawer = render(&animations, &board);
+        assert_eq!(drawer.get_color(2, 1), Some(Color::DarkGrey));
+        assert_eq!(drawer.get_color(2, 2), Some(Color::White));
+        assert_eq!(drawer.get_color(4, 2), Some(Color::White));
+
+        // the flash is over before the trail
+        animations.set_frame(LOCK_FLASH_FRAMES);
+        let drawer = render(&animations, &board);
+        assert_eq!(drawer.get_color(2, 2), None);
+        assert_eq!(drawer.get_color(2, 1), Some(Color::DarkGrey));
+    }
+
+    #[test]
+    fn disabled() {
+        let mut animations = Animations::new(false);
+        let mut board = BlocksData::new(Size { width: 4, height: 1 });
+        board.set_region(Point::new(0, 0), Size { width: 4, height: 1 }, true);
+        assert_eq!(animations.start_line_clear(board, LINE_CLEAR_FRAMES), 0);
+        animations.flash_lock(vec![Point::new(0, 0)]);
+        assert!(!animations.set_frame(1));
+    }
+}
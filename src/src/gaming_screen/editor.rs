@@ -0,0 +1,101 @@
+use std::time::Duration;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crate::gaming_screen::GamingScreen;
+use crate::gaming_screen::data::bricks::BrickType;
+use crate::input::{poll, read, Action};
+use crate::screens::NextScreen;
+use crate::tetris::{Point, Size};
+
+impl GamingScreen<'_> {
+    // paint blocks and choose the next pieces until the start key is pressed,
+    // returns the next screen if editing is left with the exit key
+    pub(super) fn edit_board(&mut self) -> std::io::Result<Option<NextScreen>> {
+        self.editing = true;
+        self.core.manager.remove_current_brick();
+        if let Some(brick) = self.core.next_brick.take() {
+            // the next piece goes back to the queue
+            if let Some(practice) = self.core.practice.as_mut() {
+                if let Some(brick_type) = self.core.brick_collection.find_type(&brick) {
+                    practice.queue.push_front(brick_type);
+                }
+            }
+        }
+        self.info_view.update_next_brick(None);
+
+        let size = self.core.manager.blocks.get_size();
+        let settings = self.settings;
+        let mut cursor = Point::new(0, size.height - 1);
+        self.render_editor(cursor);
+        'editing: loop {
+            self.drawer.flush();
+            if !poll(Duration::from_millis(500))? {
+                continue;
+            }
+            let event = read()?;
+            if let Event::Resize(_, _) = event {
+                self.init();
+            }
+            for action in settings.key_bindings.get_actions(&event) {
+                match action {
+                    Action::Exit => {
+                        self.editing = false;
+                        return Ok(Some(NextScreen::Welcome));
+                    }
+                    Action::Confirm => break 'editing,
+                    Action::Left if cursor.x > 0 => cursor.x -= 1,
+                    Action::Right if cursor.x + 1 < size.width => cursor.x += 1,
+                    Action::Up if cursor.y > 0 => cursor.y -= 1,
+                    Action::Down if cursor.y + 1 < size.height => cursor.y += 1,
+                    Action::ToggleBlock => {
+                        let filled = self.core.manager.blocks.blocks[cursor.y as usize][cursor.x as usize];
+                        self.core.manager.set_region(cursor, Size { width: 1, height: 1 }, !filled);
+                    }
+                    Action::FillLine => self.fill_line(cursor),
+                    _ => {}
+                }
+            }
+            // the queue is typed as piece letters
+            if let (Some(practice), Event::Key(event @ KeyEvent { kind: KeyEventKind::Press | KeyEventKind::Repeat, .. })) = (self.core.practice.as_mut(), event) {
+                if event.code == KeyCode::Backspace {
+                    practice.queue.pop_back();
+                }
+                if let KeyCode::Char(c) = event.code {
+                    if let Some(brick_type) = BrickType::from_char(c) {
+                        practice.queue.push_back(brick_type);
+                    }
+                }
+            }
+            self.render_editor(cursor);
+        }
+
+        if let Some(practice) = self.core.practice.as_mut() {
+            practice.save_setup(&self.core.manager.blocks);
+        }
+        self.core.next_brick = self.core.get_new_brick();
+        self.editing = false;
+        self.render_game();
+        self.update_bricks();
+        self.update_puzzle_info();
+        self.info_view.render_data();
+        Ok(None)
+    }
+
+    // fill the line except the block under the cursor, or clear it if it is filled already
+    fn fill_line(&mut self, cursor: Point) {
+        let manager = &mut self.core.manager;
+        let width = manager.blocks.get_size().width;
+        let line = &manager.blocks.blocks[cursor.y as usize];
+        let filled = line.iter().enumerate().all(|(x, block)| *block || x == cursor.x as usize);
+        manager.set_region(Point::new(0, cursor.y), Size { width, height: 1 }, !filled);
+        if !filled {
+            manager.set_region(cursor, Size { width: 1, height: 1 }, false);
+        }
+    }
+
+    fn render_editor(&mut self, cursor: Point) {
+        self.render_game();
+        self.game_view.render_cursor(cursor);
+        self.update_puzzle_info();
+        self.info_view.render_data();
+    }
+}
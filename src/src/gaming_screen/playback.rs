@@ -0,0 +1,138 @@
+use std::time::{Duration, Instant};
+use crossterm::event::Event;
+use crate::gaming_screen::GamingScreen;
+use tetris_engine::game::NextResult;
+use crate::gaming_screen::data::replay::{format_duration, Replay};
+use crate::input::{poll, read, Action};
+use crate::screens::NextScreen;
+
+static SPEEDS: [f64; 4] = [0.5, 1.0, 2.0, 4.0];
+static SEEK_SECONDS: u32 = 10;
+
+// how far a replay is played
+struct ReplayCursor {
+    tick: u32,
+    // index of the next input to apply
+    input: usize,
+}
+
+impl GamingScreen<'_> {
+    // play a recorded game again, space pauses, up and down change the speed,
+    // left and right seek, the exit key goes back to the list
+    pub fn play_replay(&mut self, replay: &Replay) -> std::io::Result<NextScreen> {
+        self.start_replay(replay);
+        self.init();
+        self.render();
+        let settings = self.settings;
+        let tick_duration = replay.mode.tick_duration();
+        let ticks_per_second = (Duration::from_secs(1).as_nanos() / tick_duration.as_nanos()) as u32;
+        let mut cursor = ReplayCursor { tick: 0, input: 0 };
+        let mut speed = 1;
+        let mut paused = false;
+        let mut last_tick_time = Instant::now();
+        loop {
+            let scaled_tick_duration = tick_duration.div_f64(SPEEDS[speed]);
+            self.render_replay_status(replay, &cursor, SPEEDS[speed], paused);
+            self.drawer.flush();
+            let timeout = (last_tick_time + scaled_tick_duration)
+                .saturating_duration_since(Instant::now())
+                .min(Duration::from_millis(100));
+            if poll(timeout)? {
+                let event = read()?;
+                if let Event::Resize(_, _) = event {
+                    self.init();
+                    self.render();
+                }
+                let actions = settings.key_bindings.get_actions(&event);
+                for action in actions.iter() {
+                    match action {
+                        Action::Exit => return Ok(NextScreen::Replays),
+                        Action::Pause => paused = !paused,
+                        Action::Up if speed + 1 < SPEEDS.len() => speed += 1,
+                        Action::Down if speed > 0 => speed -= 1,
+                        Action::Left => {
+                            let target = cursor.tick.saturating_sub(SEEK_SECONDS * ticks_per_second);
+                            cursor = self.seek_replay(replay, cursor, target);
+                        }
+                        Action::Right => {
+                            let target = (cursor.tick + SEEK_SECONDS * ticks_per_second).min(replay.ticks);
+                            cursor = self.seek_replay(replay, cursor, target);
+                        }
+                        _ => {}
+                    }
+                }
+                if !actions.is_empty() {
+                    last_tick_time = Instant::now();
+                }
+                continue;
+            }
+            if paused {
+                last_tick_time = Instant::now();
+                continue;
+            }
+            let tick = cursor.tick;
+            while Instant::now() - last_tick_time >= scaled_tick_duration && cursor.tick < replay.ticks {
+                last_tick_time += scaled_tick_duration;
+                self.play_replay_tick(replay, &mut cursor);
+            }
+            if cursor.tick != tick {
+                self.render();
+            }
+            if cursor.tick >= replay.ticks {
+                self.render_replay_status(replay, &cursor, SPEEDS[speed], paused);
+                self.wait_for_any_key("REPLAY END")?;
+                return Ok(NextScreen::Replays);
+            }
+        }
+    }
+
+    fn start_replay(&mut self, replay: &Replay) {
+        self.start_game(replay.mode, replay.seed);
+        self.recording = None;
+    }
+
+    // go to the tick without drawing the ticks in between, going back starts over
+    fn seek_replay(&mut self, replay: &Replay, mut cursor: ReplayCursor, target: u32) -> ReplayCursor {
+        if target < cursor.tick {
+            self.start_replay(replay);
+            cursor = ReplayCursor { tick: 0, input: 0 };
+        }
+        while cursor.tick < target {
+            self.play_replay_tick(replay, &mut cursor);
+        }
+        self.render();
+        cursor
+    }
+
+    // the inputs before the tick and the tick
+    fn play_replay_tick(&mut self, replay: &Replay, cursor: &mut ReplayCursor) {
+        while let Some((_, input)) = replay.inputs.get(cursor.input).filter(|(tick, _)| *tick == cursor.tick) {
+            self.apply_input(*input);
+            cursor.input += 1;
+        }
+        cursor.tick += 1;
+        if self.time_tick() == NextResult::GameOver {
+            cursor.tick = replay.ticks;
+        }
+    }
+
+    // shown on the bottom of the game view
+    fn render_replay_status(&self, replay: &Replay, cursor: &ReplayCursor, speed: f64, paused: bool) {
+        let tick_duration = replay.mode.tick_duration();
+        let state = if paused { "||" } else { ">>" };
+        let text = format!(
+            " {} {}x {}/{} ",
+            state,
+            speed,
+            format_duration(tick_duration * cursor.tick),
+            format_duration(tick_duration * replay.ticks)
+        );
+        let region = self.settings.gaming_region;
+        // centered on the bottom side of the frame
+        let side = self.drawer.get_theme().frame.horizontal.to_string();
+        let padding = (region.width as usize - 2).saturating_sub(text.len());
+        let text = format!("{}{}{}", side.repeat(padding / 2), text, side.repeat(padding - padding / 2));
+        self.drawer.draw_string(1, region.height - 1, &text, None);
+    }
+}
+
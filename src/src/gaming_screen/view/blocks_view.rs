@@ -1,12 +1,27 @@
+use std::time::Duration;
+use crossterm::style::Color;
 use crate::drawer::Drawer;
 use crate::gaming_screen::data::BlocksData;
 use crate::tetris::Point;
 
+static FADING_DURATION: Duration = Duration::from_secs(4);
+static SHADED_TOP: &str = "▀▀";
+static SHADED_FILL: &str = "  ";
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum StackVisibility {
+    Visible,
+    Invisible,
+    Fading,
+}
+
 pub(crate) struct BlocksView<'a> {
     blocks_start_point: Point,
     drawer: &'a dyn Drawer,
-    block_text: String,
-    empty_text: String,
+    visibility: StackVisibility,
+    // how long a tick of the board lasts, blocks fade by the ticks since they were locked
+    tick_duration: Duration,
+    scale: u16,
 }
 
 pub trait BlocksRenderView {
@@ -16,17 +31,27 @@ pub trait BlocksRenderView {
 impl BlocksRenderView for BlocksView<'_> {
     fn render_blocks(&self, data: &BlocksData) {
         let blocks = &data.blocks;
+        let theme = self.drawer.get_theme();
         for y in 0..blocks.len() {
             let line = &blocks[y];
             for x in 0..line.len() {
-                let point = Point {
-                    x: self.blocks_start_point.x + ((x * 2) as u16),
-                    y: self.blocks_start_point.y + y as u16,
-                };
-                let text = if blocks[y][x] { &self.block_text } else {
-                    &self.empty_text
+                let shown = if blocks[y][x] {
+                    self.get_block_color(data.locked_at[y][x], theme.get_block_color(data.kinds[y][x]), data.tick)
+                } else {
+                    None
                 };
-                self.drawer.draw_string_on_point(point, text, None);
+                for dy in 0..self.scale {
+                    for dx in 0..self.scale {
+                        let point = Point {
+                            x: self.blocks_start_point.x + (x as u16 * self.scale + dx) * 2,
+                            y: self.blocks_start_point.y + y as u16 * self.scale + dy,
+                        };
+                        match shown {
+                            Some(color) => self.render_block(point, color, dy == 0),
+                            None => self.drawer.draw_string_on_point(point, &theme.empty, None),
+                        }
+                    }
+                }
             }
         }
     }
@@ -34,15 +59,177 @@ impl BlocksRenderView for BlocksView<'_> {
 
 impl BlocksView<'_> {
     pub(crate) fn new<'a>(blocks_start_point: Point, drawer: &'a dyn Drawer) -> BlocksView<'a> {
-        let block_text = String::from("■");
-        let empty_text = String::from("  ");
-
         let component = BlocksView {
             blocks_start_point,
             drawer,
-            block_text,
-            empty_text,
+            visibility: StackVisibility::Visible,
+            tick_duration: Duration::ZERO,
+            scale: 1,
         };
         component
     }
-}
\ No newline at end of file
+
+    pub(crate) fn set_visibility(&mut self, visibility: StackVisibility) {
+        self.visibility = visibility;
+    }
+
+    pub(crate) fn set_tick_duration(&mut self, tick_duration: Duration) {
+        self.tick_duration = tick_duration;
+    }
+
+    // draw a text over a single block
+    pub(crate) fn render_text_at(&self, block: Point, text: &String, color: Option<Color>) {
+        for dy in 0..self.scale {
+            for dx in 0..self.scale {
+                let point = Point {
+                    x: self.blocks_start_point.x + (block.x * self.scale + dx) * 2,
+                    y: self.blocks_start_point.y + block.y * self.scale + dy,
+                };
+                self.drawer.draw_string_on_point(point, text, color);
+            }
+        }
+    }
+
+    pub(crate) fn set_start_point(&mut self, blocks_start_point: Point) {
+        self.blocks_start_point = blocks_start_point;
+    }
+
+    pub(crate) fn set_scale(&mut self, scale: u16) {
+        self.scale = scale;
+    }
+
+    // a shaded block is filled with its color, the upper half of its top row in the shade
+    fn render_block(&self, point: Point, color: Option<Color>, top: bool) {
+        let theme = self.drawer.get_theme();
+        match (color, color.and_then(|color| theme.get_shade(color))) {
+            (Some(color), Some(shade)) if top => {
+                self.drawer.draw_string_on_background(point.x, point.y, &String::from(SHADED_TOP), Some(shade), color)
+            }
+            (Some(color), Some(_)) => self.drawer.draw_string_on_background(point.x, point.y, &String::from(SHADED_FILL), None, color),
+            _ => self.drawer.draw_string_on_point(point, &theme.block, color),
+        }
+    }
+
+    // None if the block should not be drawn, otherwise the color to draw it with
+    fn get_block_color(&self, locked_at: Option<u32>, color: Option<Color>, tick: u32) -> Option<Option<Color>> {
+        let locked_at = match locked_at {
+            // the falling brick is always visible
+            None => return Some(color),
+            Some(locked_at) => locked_at,
+        };
+        match self.visibility {
+            StackVisibility::Visible => Some(color),
+            StackVisibility::Invisible => None,
+            StackVisibility::Fading => {
+                let age = self.tick_duration * tick.saturating_sub(locked_at);
+                if age < FADING_DURATION / 4 {
+                    Some(color)
+                } else if age < FADING_DURATION / 2 {
+                    Some(Some(Color::Grey))
+                } else if age < FADING_DURATION {
+                    Some(Some(Color::DarkGrey))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crossterm::style::Color;
+    use crate::drawer::CaptureDrawer;
+    use crate::gaming_screen::data::BlocksData;
+    use crate::gaming_screen::data::bricks::{BrickCollection, BrickType};
+    use crate::gaming_screen::view::blocks_view::*;
+    use crate::tetris::{Point, Size};
+    use crate::theme::Theme;
+
+    #[test]
+    fn render_blocks() {
+        let drawer = CaptureDrawer::new(Size { width: 10, height: 5 });
+        let mut view = BlocksView::new(Point::new(1, 1), &drawer);
+        let mut data = BlocksData::new(Size { width: 4, height: 3 });
+        data.set_region(Point::new(0, 0), Size { width: 1, height: 3 }, true);
+        data.set_region(Point::new(0, 2), Size { width: 4, height: 1 }, true);
+        view.render_blocks(&data);
+        assert_eq!(drawer.get_lines(), vec![
+            "",
+            " ■",
+            " ■",
+            " ■■■■",
+            "",
+        ]);
+
+        // a big block is drawn two by two
+        view.set_scale(2);
+        let mut data = BlocksData::new(Size { width: 2, height: 1 });
+        data.set_region(Point::new(1, 0), Size { width: 1, height: 1 }, true);
+        view.render_blocks(&data);
+        assert_eq!(drawer.get_lines(), vec![
+            "",
+            "     ■■",
+            "     ■■",
+            " ■■■■",
+            "",
+        ]);
+    }
+
+    #[test]
+    fn fading_blocks() {
+        let drawer = CaptureDrawer::new(Size { width: 6, height: 1 });
+        let mut view = BlocksView::new(Point::new(0, 0), &drawer);
+        view.set_visibility(StackVisibility::Fading);
+        view.set_tick_duration(Duration::from_millis(500));
+        let mut data = BlocksData::new(Size { width: 3, height: 1 });
+        data.set_region(Point::new(0, 0), Size { width: 3, height: 1 }, true);
+        data.tick = 20;
+        data.locked_at[0][1] = Some(17);
+        data.locked_at[0][2] = Some(10);
+        view.render_blocks(&data);
+        // the oldest block is gone already
+        assert_eq!(drawer.get_lines(), vec![
+            "■■",
+        ]);
+        assert_eq!(drawer.get_color(0, 0), None);
+        assert_eq!(drawer.get_color(2, 0), Some(Color::Grey));
+    }
+
+    #[test]
+    fn themed_blocks() {
+        let drawer = CaptureDrawer::with_theme(Size { width: 8, height: 2 }, Theme::find("ascii").unwrap());
+        let view = BlocksView::new(Point::new(0, 0), &drawer);
+        let mut data = BlocksData::new(Size { width: 4, height: 2 });
+        data.set_brick(&BrickCollection::new().get_new_one(BrickType::T, 0), true);
+        view.render_blocks(&data);
+        assert_eq!(drawer.get_lines(), vec![
+            " .[] . .",
+            "[][][] .",
+        ]);
+        assert_eq!(drawer.get_color(2, 0), Some(Color::Magenta));
+        assert_eq!(drawer.get_color(0, 0), None);
+    }
+
+    #[test]
+    fn shaded_blocks() {
+        let theme = Theme::find("neon").unwrap();
+        let drawer = CaptureDrawer::with_theme(Size { width: 8, height: 4 }, theme.clone());
+        let mut view = BlocksView::new(Point::new(0, 0), &drawer);
+        view.set_scale(2);
+        let mut data = BlocksData::new(Size { width: 2, height: 2 });
+        data.set_brick(&BrickCollection::new().get_new_one(BrickType::O, 0), true);
+        view.render_blocks(&data);
+        assert_eq!(drawer.get_lines(), vec![
+            "▀▀▀▀▀▀▀▀",
+            "",
+            "▀▀▀▀▀▀▀▀",
+            "",
+        ]);
+        let color = theme.get_block_color(Some(BrickType::O)).unwrap();
+        assert_eq!(drawer.get_color(0, 0), theme.get_shade(color));
+        assert_eq!(drawer.get_background(0, 0), Some(color));
+        assert_eq!(drawer.get_background(6, 1), Some(color));
+    }
+}
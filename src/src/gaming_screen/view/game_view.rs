@@ -1,7 +1,9 @@
+use std::time::Duration;
+use crossterm::style::Color;
 use crate::drawer::Drawer;
 use crate::gaming_screen::data::BlocksData;
 use crate::gaming_screen::data::game_panel::{GamePanel};
-use crate::gaming_screen::view::blocks_view::{BlocksRenderView, BlocksView};
+use crate::gaming_screen::view::blocks_view::{BlocksRenderView, BlocksView, StackVisibility};
 use crate::tetris::{AppSettings, Point, Size};
 
 pub(crate) struct GameView<'a> {
@@ -15,6 +17,46 @@ impl GameView<'_> {
     pub fn init(&self) {
         self.drawer.draw_frame(self.start_point.x, self.start_point.y, self.size.width, self.size.height);
     }
+
+    pub fn set_stack_visibility(&mut self, visibility: StackVisibility) {
+        self.blocks_view.set_visibility(visibility);
+    }
+
+    pub fn set_tick_duration(&mut self, tick_duration: Duration) {
+        self.blocks_view.set_tick_duration(tick_duration);
+    }
+
+    pub fn render_cursor(&self, block: Point) {
+        self.blocks_view.render_text_at(block, &String::from("[]"), Some(Color::Yellow));
+    }
+
+    // a block over the board, whatever the board has there
+    pub fn render_block_at(&self, block: Point, color: Color) {
+        self.blocks_view.render_text_at(block, &self.drawer.get_theme().block, Some(color));
+    }
+
+    pub fn render_empty_at(&self, block: Point) {
+        self.blocks_view.render_text_at(block, &self.drawer.get_theme().empty, None);
+    }
+
+    // the garbage waiting to rise, as a bar from the bottom in the column left of the board
+    pub fn render_garbage_meter(&self, rows: u16) {
+        let banner = self.drawer.get_theme().banner.to_string();
+        let empty = String::from(" ");
+        let height = self.size.height - 2;
+        for row in 0..height {
+            let y = self.start_point.y + height - row;
+            if row < rows {
+                self.drawer.draw_string(self.start_point.x + 1, y, &banner, Some(Color::Red));
+            } else {
+                self.drawer.draw_string(self.start_point.x + 1, y, &empty, None);
+            }
+        }
+    }
+
+    pub fn set_block_scale(&mut self, scale: u16) {
+        self.blocks_view.set_scale(scale);
+    }
 }
 
 impl BlocksRenderView for GameView<'_> {
@@ -24,17 +66,8 @@ impl BlocksRenderView for GameView<'_> {
 }
 
 impl GameView<'_> {
-    pub(crate) fn new<'a>(region_size: Size, drawer: &'a dyn Drawer) -> GameView<'a> {
-        let start_point = Point {
-            x: 0,
-            y: 0,
-        };
-        let blocks_start_point = Point {
-            x: start_point.x + 2,
-            y: start_point.y + 1,
-        };
-
-        let blocks_view = BlocksView::new(blocks_start_point, drawer);
+    pub(crate) fn new<'a>(start_point: Point, region_size: Size, drawer: &'a dyn Drawer) -> GameView<'a> {
+        let blocks_view = BlocksView::new(GameView::get_blocks_start_point(start_point), drawer);
 
         let component = GameView {
             size: region_size,
@@ -44,5 +77,70 @@ impl GameView<'_> {
         };
         component
     }
+
+    // the blocks are inside the frame
+    fn get_blocks_start_point(start_point: Point) -> Point {
+        Point {
+            x: start_point.x + 2,
+            y: start_point.y + 1,
+        }
+    }
+
+    // lay the view out again at another place, it is drawn by the next init
+    pub fn move_to(&mut self, start_point: Point) {
+        self.start_point = start_point;
+        self.blocks_view.set_start_point(GameView::get_blocks_start_point(start_point));
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Color;
+    use crate::drawer::{CaptureDrawer, Drawer};
+    use crate::gaming_screen::data::BlocksData;
+    use crate::gaming_screen::view::blocks_view::BlocksRenderView;
+    use crate::gaming_screen::view::game_view::*;
+    use crate::tetris::{AppSettings, Point, Size};
+
+    #[test]
+    fn render() {
+        let settings = AppSettings::default();
+        let drawer = CaptureDrawer::new(settings.gaming_region);
+        drawer.resize(&settings.gaming_region);
+        let game_view = GameView::new(Point::new(0, 0), settings.gaming_region, &drawer);
+        let mut data = BlocksData::new(settings.gaming_blocks_size);
+        data.set_region(Point::new(0, 19), Size { width: 9, height: 1 }, true);
+        data.set_region(Point::new(4, 0), Size { width: 2, height: 2 }, true);
+        game_view.init();
+        game_view.render_blocks(&data);
+        game_view.render_cursor(Point::new(9, 19));
+        game_view.render_garbage_meter(2);
+        assert_eq!(drawer.get_lines(), vec![
+            "┏━━━━━━━━━━━━━━━━━━━━━┓",
+            "┃         ■■        ┃",
+            "┃         ■■        ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃                     ┃",
+            "┃█                    ┃",
+            "┃█■■■■■■■■■[]┃",
+            "┗━━━━━━━━━━━━━━━━━━━━━┛",
+        ]);
+        assert_eq!(drawer.get_color(20, 20), Some(Color::Yellow));
+        assert_eq!(drawer.get_color(1, 19), Some(Color::Red));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::drawer::Drawer;
 use crate::gaming_screen::data::BlocksData;
 use crate::gaming_screen::data::bricks::Brick;
+use crate::gaming_screen::data::master::{format_frames, MasterInfo, MAX_LEVEL, SECTION_LEVELS};
 use crate::gaming_screen::view::blocks_view::{BlocksRenderView, BlocksView};
 use crate::tetris::{AppSettings, Point, Size};
 
@@ -9,38 +10,215 @@ static NEXT_BRICK_REGION_SIZE: Size = Size {
     width: 4,
 };
 
-pub struct InfoView<'a> {
+// where everything of the info view is drawn
+struct InfoLayout {
     start_point: Point,
     score_title_point: Point,
     score_text_point: Point,
     lines_count_title_point: Point,
     lines_count_text_point: Point,
-    next_brick: Option<Brick>,
+    level_title_point: Point,
+    level_text_point: Point,
+    grade_title_point: Point,
+    grade_text_point: Point,
+    section_title_point: Point,
+    section_text_point: Point,
+    last_section_text_point: Point,
+    goal_title_point: Point,
+    goal_text_point: Point,
+    pieces_title_point: Point,
+    pieces_text_point: Point,
+    queue_title_point: Point,
+    queue_text_point: Point,
+    editing_text_point: Point,
     next_brick_title_point: Point,
     next_brick_view_point: Point,
+    hold_brick_title_point: Point,
+    hold_brick_view_point: Point,
+}
+
+impl InfoLayout {
+    fn new(start_point: Point) -> InfoLayout {
+        // the next and the held brick side by side
+        let next_brick_title_point = Point {
+            x: start_point.x + 2,
+            y: start_point.y + 2,
+        };
+        let next_brick_view_point = Point {
+            y: next_brick_title_point.y + 1,
+            ..next_brick_title_point
+        };
+        let hold_brick_title_point = Point {
+            x: next_brick_title_point.x + 8,
+            ..next_brick_title_point
+        };
+        let hold_brick_view_point = Point {
+            y: hold_brick_title_point.y + 1,
+            ..hold_brick_title_point
+        };
+        let score_title_point = Point {
+            x: start_point.x + 6,
+            y: next_brick_view_point.y + 5,
+        };
+        let score_text_point = Point {
+            y: score_title_point.y + 1,
+            ..score_title_point
+        };
+        let lines_count_title_point = Point {
+            y: score_text_point.y + 2,
+            ..score_text_point
+        };
+        let lines_count_text_point = Point {
+            y: lines_count_title_point.y + 1,
+            ..lines_count_title_point
+        };
+        let level_title_point = Point {
+            y: lines_count_text_point.y + 2,
+            ..lines_count_text_point
+        };
+        let level_text_point = Point {
+            y: level_title_point.y + 1,
+            ..level_title_point
+        };
+        let grade_title_point = Point {
+            y: level_text_point.y + 1,
+            ..level_text_point
+        };
+        let grade_text_point = Point {
+            y: grade_title_point.y + 1,
+            ..grade_title_point
+        };
+        let section_title_point = Point {
+            y: grade_text_point.y + 1,
+            ..grade_text_point
+        };
+        let section_text_point = Point {
+            y: section_title_point.y + 1,
+            ..section_title_point
+        };
+        let last_section_text_point = Point {
+            y: section_text_point.y + 1,
+            ..section_text_point
+        };
+        let goal_title_point = level_title_point;
+        let goal_text_point = level_text_point;
+        let pieces_title_point = Point {
+            y: goal_text_point.y + 2,
+            ..goal_text_point
+        };
+        let pieces_text_point = Point {
+            y: pieces_title_point.y + 1,
+            ..pieces_title_point
+        };
+        let queue_title_point = level_title_point;
+        let queue_text_point = level_text_point;
+        let editing_text_point = Point {
+            y: queue_text_point.y + 2,
+            ..queue_text_point
+        };
+        InfoLayout {
+            start_point,
+            score_title_point,
+            score_text_point,
+            lines_count_title_point,
+            lines_count_text_point,
+            level_title_point,
+            level_text_point,
+            grade_title_point,
+            grade_text_point,
+            section_title_point,
+            section_text_point,
+            last_section_text_point,
+            goal_title_point,
+            goal_text_point,
+            pieces_title_point,
+            pieces_text_point,
+            queue_title_point,
+            queue_text_point,
+            editing_text_point,
+            next_brick_title_point,
+            next_brick_view_point,
+            hold_brick_title_point,
+            hold_brick_view_point,
+        }
+    }
+}
+
+pub struct InfoView<'a> {
+    layout: InfoLayout,
+    master_info: Option<MasterInfo>,
+    puzzle_goal: Option<String>,
+    puzzle_pieces_left: usize,
+    practice_queue: Option<String>,
+    editing: bool,
+    next_brick: Option<Brick>,
+    hold_brick: Option<Brick>,
     size: Size,
     drawer: &'a dyn Drawer,
     lines_count: u32,
     score: f64,
     blocks: BlocksData,
     blocks_view: BlocksView<'a>,
+    hold_blocks: BlocksData,
+    hold_blocks_view: BlocksView<'a>,
 }
 
 impl InfoView<'_> {
     pub fn render_data(&self) {
-        self.drawer.draw_string_on_point(self.score_text_point, &self.score.to_string(), None);
-        self.drawer.draw_string_on_point(self.lines_count_text_point, &self.lines_count.to_string(), None);
-        if self.next_brick.is_some() {
-            self.blocks_view.render_blocks(&self.blocks);
+        self.drawer.draw_string_on_point(self.layout.score_text_point, &self.score.to_string(), None);
+        self.drawer.draw_string_on_point(self.layout.lines_count_text_point, &self.lines_count.to_string(), None);
+        self.blocks_view.render_blocks(&self.blocks);
+        self.hold_blocks_view.render_blocks(&self.hold_blocks);
+        self.render_master_data();
+        if let Some(goal) = &self.puzzle_goal {
+            self.drawer.draw_string_on_point(self.layout.goal_text_point, goal, None);
+            let pieces_text = format!("{:<3}", self.puzzle_pieces_left);
+            self.drawer.draw_string_on_point(self.layout.pieces_text_point, &pieces_text, None);
+        }
+        if let Some(queue) = &self.practice_queue {
+            // the queue is cut to the width of the info view
+            let queue_text = format!("{:<12.12}", queue);
+            self.drawer.draw_string_on_point(self.layout.queue_text_point, &queue_text, None);
+            let editing_text = if self.editing { "Editing" } else { "       " };
+            self.drawer.draw_string_on_point(self.layout.editing_text_point, &String::from(editing_text), None);
+        }
+    }
+
+    pub fn render_master_data(&self) {
+        if let Some(info) = self.master_info {
+            let drawer = self.drawer;
+            let next_level = (info.level / SECTION_LEVELS + 1) * SECTION_LEVELS;
+            let level_text = format!("{:>3}/{:<3}", info.level, next_level.min(MAX_LEVEL));
+            drawer.draw_string_on_point(self.layout.level_text_point, &level_text, None);
+            drawer.draw_string_on_point(self.layout.grade_text_point, &format!("{:<2}", info.grade), None);
+            drawer.draw_string_on_point(self.layout.section_text_point, &format_frames(info.section_frames), None);
+            let last_section_text = match info.last_section_frames {
+                Some(frames) => format_frames(frames),
+                None => String::from("--:--:--"),
+            };
+            drawer.draw_string_on_point(self.layout.last_section_text_point, &last_section_text, None);
         }
     }
 
     pub fn init(&self) {
         let drawer = self.drawer;
-        drawer.draw_frame(self.start_point.x, self.start_point.y, self.size.width, self.size.height);
-        drawer.draw_string_on_point(self.next_brick_title_point, &String::from("Next"), None);
-        drawer.draw_string_on_point(self.score_title_point, &String::from("Scores"), None);
-        drawer.draw_string_on_point(self.lines_count_title_point, &String::from("Lines"), None);
+        drawer.draw_frame(self.layout.start_point.x, self.layout.start_point.y, self.size.width, self.size.height);
+        drawer.draw_string_on_point(self.layout.next_brick_title_point, &String::from("Next"), None);
+        drawer.draw_string_on_point(self.layout.hold_brick_title_point, &String::from("Hold"), None);
+        drawer.draw_string_on_point(self.layout.score_title_point, &String::from("Scores"), None);
+        drawer.draw_string_on_point(self.layout.lines_count_title_point, &String::from("Lines"), None);
+        if self.master_info.is_some() {
+            drawer.draw_string_on_point(self.layout.level_title_point, &String::from("Level"), None);
+            drawer.draw_string_on_point(self.layout.grade_title_point, &String::from("Grade"), None);
+            drawer.draw_string_on_point(self.layout.section_title_point, &String::from("Section"), None);
+        }
+        if self.puzzle_goal.is_some() {
+            drawer.draw_string_on_point(self.layout.goal_title_point, &String::from("Goal"), None);
+            drawer.draw_string_on_point(self.layout.pieces_title_point, &String::from("Pieces"), None);
+        }
+        if self.practice_queue.is_some() {
+            drawer.draw_string_on_point(self.layout.queue_title_point, &String::from("Queue"), None);
+        }
         self.render_data();
     }
 
@@ -49,6 +227,12 @@ impl InfoView<'_> {
         self.lines_count = 0;
         self.blocks.set_region(Point::new(0, 0), NEXT_BRICK_REGION_SIZE, false);
         self.next_brick = None;
+        self.hold_blocks.set_region(Point::new(0, 0), NEXT_BRICK_REGION_SIZE, false);
+        self.hold_brick = None;
+        self.master_info = None;
+        self.puzzle_goal = None;
+        self.practice_queue = None;
+        self.editing = false;
         self.render_data();
     }
 }
@@ -59,50 +243,37 @@ impl InfoView<'_> {
             x: settings.gaming_region.width,
             y: 0,
         };
-        let next_brick_title_point = Point {
-            x: start_point.x + 6,
-            y: start_point.y + 2,
-        };
-        let next_brick_view_point = Point {
-            y: next_brick_title_point.y + 1,
-            ..next_brick_title_point
-        };
-        let score_title_point = Point {
-            y: next_brick_view_point.y + 5,
-            ..next_brick_view_point
-        };
-        let score_text_point = Point {
-            y: score_title_point.y + 1,
-            ..score_title_point
-        };
-        let lines_count_title_point = Point {
-            y: score_text_point.y + 2,
-            ..score_text_point
-        };
-        let lines_count_text_point = Point {
-            y: lines_count_title_point.y + 1,
-            ..lines_count_title_point
-        };
-        let view = BlocksView::new(next_brick_view_point, drawer);
+        let layout = InfoLayout::new(start_point);
+        let view = BlocksView::new(layout.next_brick_view_point, drawer);
+        let hold_view = BlocksView::new(layout.hold_brick_view_point, drawer);
         let component = InfoView {
+            layout,
             size: settings.info_region,
-            start_point,
-            score_title_point,
-            score_text_point,
-            lines_count_title_point,
-            lines_count_text_point,
+            master_info: None,
+            puzzle_goal: None,
+            puzzle_pieces_left: 0,
+            practice_queue: None,
+            editing: false,
             lines_count: 0,
             score: 0f64,
             drawer,
             next_brick: None,
-            next_brick_title_point,
-            next_brick_view_point,
+            hold_brick: None,
             blocks: BlocksData::new(NEXT_BRICK_REGION_SIZE),
             blocks_view: view,
+            hold_blocks: BlocksData::new(NEXT_BRICK_REGION_SIZE),
+            hold_blocks_view: hold_view,
         };
         component
     }
 
+    // lay the view out again at another place, it is drawn by the next init
+    pub fn move_to(&mut self, start_point: Point) {
+        self.layout = InfoLayout::new(start_point);
+        self.blocks_view.set_start_point(self.layout.next_brick_view_point);
+        self.hold_blocks_view.set_start_point(self.layout.hold_brick_view_point);
+    }
+
     pub fn add_lines(&mut self, count: u16) {
         self.lines_count = self.lines_count + count as u32;
         let score = match count {
@@ -116,11 +287,94 @@ impl InfoView<'_> {
         self.score = self.score + score;
     }
 
-    pub fn update_next_brick(&mut self, brick: Brick) {
+    pub fn get_score(&self) -> (f64, u32) {
+        (self.score, self.lines_count)
+    }
+
+    pub fn set_score(&mut self, score: f64, lines_count: u32) {
+        self.score = score;
+        self.lines_count = lines_count;
+    }
+
+    pub fn update_master_info(&mut self, info: MasterInfo) {
+        self.score = info.score as f64;
+        self.lines_count = info.lines;
+        self.master_info = Some(info);
+    }
+
+    pub fn update_practice_info(&mut self, queue: String, editing: bool) {
+        self.practice_queue = Some(queue);
+        self.editing = editing;
+    }
+
+    pub fn update_next_brick(&mut self, brick: Option<Brick>) {
         if self.next_brick.is_some() {
             self.blocks.set_brick(&self.next_brick.unwrap(), false);
         }
-        self.next_brick = Some(brick);
-        self.blocks.set_brick(&self.next_brick.unwrap(), true);
+        self.next_brick = brick;
+        if let Some(brick) = &self.next_brick {
+            self.blocks.set_brick(brick, true);
+        }
+    }
+
+    pub fn update_hold_brick(&mut self, brick: Option<Brick>) {
+        if let Some(old_brick) = &self.hold_brick {
+            self.hold_blocks.set_brick(old_brick, false);
+        }
+        self.hold_brick = brick;
+        if let Some(brick) = &self.hold_brick {
+            self.hold_blocks.set_brick(brick, true);
+        }
+    }
+
+    pub fn update_puzzle_info(&mut self, goal: String, pieces_left: usize) {
+        self.puzzle_goal = Some(goal);
+        self.puzzle_pieces_left = pieces_left;
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::drawer::{CaptureDrawer, Drawer};
+    use crate::gaming_screen::data::bricks::{BrickCollection, BrickType};
+    use crate::gaming_screen::view::info_view::*;
+    use crate::tetris::{AppSettings, Point};
+
+    #[test]
+    fn render() {
+        let settings = AppSettings::default();
+        let drawer = CaptureDrawer::new(settings.info_region);
+        drawer.resize(&settings.info_region);
+        let mut info_view = InfoView::new(&settings, &drawer);
+        info_view.move_to(Point::new(0, 0));
+        let bricks = BrickCollection::new();
+        info_view.update_next_brick(Some(bricks.get_new_one(BrickType::T, 0)));
+        info_view.update_hold_brick(Some(bricks.get_new_one(BrickType::I, 0)));
+        info_view.add_lines(4);
+        info_view.update_puzzle_info(String::from("Clear 4"), 3);
+        info_view.init();
+        assert_eq!(drawer.get_lines(), vec![
+            "┏━━━━━━━━━━━━━━━━━━┓",
+            "┃                  ┃",
+            "┃ Next    Hold     ┃",
+            "┃   ■    ■■■■ ┃",
+            "┃ ■■■           ┃",
+            "┃                  ┃",
+            "┃                  ┃",
+            "┃                  ┃",
+            "┃     Scores       ┃",
+            "┃     1500         ┃",
+            "┃                  ┃",
+            "┃     Lines        ┃",
+            "┃     4            ┃",
+            "┃                  ┃",
+            "┃     Goal         ┃",
+            "┃     Clear 4      ┃",
+            "┃                  ┃",
+            "┃     Pieces       ┃",
+            "┃     3            ┃",
+            "┃                  ┃",
+            "┃                  ┃",
+            "┗━━━━━━━━━━━━━━━━━━┛",
+        ]);
+    }
+}
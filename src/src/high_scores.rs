@@ -0,0 +1,90 @@
+use crate::tetris::GameMode;
+use std::sync::{Arc, Mutex};
+
+// how many games of each mode are kept
+pub static HIGH_SCORE_COUNT: usize = 10;
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct HighScore {
+    pub name: String,
+    pub mode: GameMode,
+    pub score: u32,
+    pub lines: u32,
+}
+
+// the best games of the sessions of a server, every session holds a clone of the same table
+#[derive(Clone, Default)]
+pub struct HighScores {
+    entries: Arc<Mutex<Vec<HighScore>>>,
+}
+
+impl HighScores {
+    pub fn new() -> HighScores {
+        HighScores::default()
+    }
+
+    // keeps the game if it is among the best of its mode, returns its place from 0
+    pub fn add(&self, entry: HighScore) -> Option<usize> {
+        let mut entries = self.entries.lock().unwrap();
+        // an earlier game with the same score stays ahead
+        let place = entries.iter()
+            .filter(|other| other.mode == entry.mode && other.score >= entry.score)
+            .count();
+        if place >= HIGH_SCORE_COUNT {
+            return None;
+        }
+        let index = entries.iter()
+            .position(|other| other.mode == entry.mode && other.score < entry.score)
+            .unwrap_or(entries.len());
+        let mode = entry.mode;
+        entries.insert(index, entry);
+        // the last one of the mode falls off the table
+        let mut kept = 0;
+        entries.retain(|other| {
+            if other.mode == mode {
+                kept += 1;
+            }
+            other.mode != mode || kept <= HIGH_SCORE_COUNT
+        });
+        Some(place)
+    }
+
+    // the best games of a mode, the best first
+    pub fn get_entries(&self, mode: GameMode) -> Vec<HighScore> {
+        self.entries.lock().unwrap().iter()
+            .filter(|entry| entry.mode == mode)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::high_scores::*;
+
+    fn entry(name: &str, mode: GameMode, score: u32) -> HighScore {
+        HighScore { name: String::from(name), mode, score, lines: score / 100 }
+    }
+
+    #[test]
+    fn keep_the_best() {
+        let high_scores = HighScores::new();
+        let shared = high_scores.clone();
+        assert_eq!(high_scores.add(entry("a", GameMode::Classic, 300)), Some(0));
+        assert_eq!(shared.add(entry("b", GameMode::Classic, 500)), Some(0));
+        assert_eq!(high_scores.add(entry("c", GameMode::Classic, 300)), Some(2));
+        assert_eq!(high_scores.add(entry("d", GameMode::Big, 100)), Some(0));
+        let names: Vec<String> = shared.get_entries(GameMode::Classic).into_iter().map(|entry| entry.name).collect();
+        assert_eq!(names, vec!["b", "a", "c"]);
+
+        for score in 0..HIGH_SCORE_COUNT as u32 {
+            high_scores.add(entry("e", GameMode::Classic, 1000 + score));
+        }
+        assert_eq!(high_scores.add(entry("f", GameMode::Classic, 400)), None);
+        let entries = high_scores.get_entries(GameMode::Classic);
+        assert_eq!(entries.len(), HIGH_SCORE_COUNT);
+        assert_eq!(entries[0].score, 1009);
+        // the other modes keep their own games
+        assert_eq!(high_scores.get_entries(GameMode::Big).len(), 1);
+    }
+}
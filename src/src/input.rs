@@ -0,0 +1,435 @@
+use std::cell::RefCell;
+use std::io::stdout;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::Duration;
+use crossterm::{
+    execute,
+    event::{self, Event, KeyCode, KeyEventKind, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
+    terminal::supports_keyboard_enhancement,
+};
+use crate::gaming_screen::data::replay::GameInput;
+
+// everything a key can stand for, each screen picks the actions it knows
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateCw,
+    RotateCcw,
+    Rotate180,
+    Hold,
+    Pause,
+    Exit,
+    // practice
+    Edit,
+    ToggleBlock,
+    FillLine,
+    Undo,
+    Redo,
+    Reset,
+    // menus
+    Confirm,
+    Up,
+    Down,
+    Left,
+    Right,
+    Replays,
+    Resume,
+    VersusBot,
+}
+
+impl Action {
+    // the input an action stands for while playing
+    pub fn get_game_input(self) -> Option<GameInput> {
+        match self {
+            Action::MoveLeft => Some(GameInput::Left),
+            Action::MoveRight => Some(GameInput::Right),
+            Action::SoftDrop => Some(GameInput::SoftDrop),
+            Action::HardDrop => Some(GameInput::Drop),
+            Action::RotateCw => Some(GameInput::Rotate),
+            Action::RotateCcw => Some(GameInput::RotateCcw),
+            Action::Rotate180 => Some(GameInput::Rotate180),
+            Action::Hold => Some(GameInput::Hold),
+            _ => None,
+        }
+    }
+}
+
+// turns terminal events into actions, a key may be bound to several actions
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct KeyBindings {
+    bindings: Vec<(KeyCode, Action)>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings::new(vec![
+            (KeyCode::Left, Action::MoveLeft),
+            (KeyCode::Right, Action::MoveRight),
+            (KeyCode::Char('s'), Action::SoftDrop),
+            (KeyCode::Down, Action::HardDrop),
+            (KeyCode::Up, Action::RotateCw),
+            (KeyCode::Char('z'), Action::RotateCcw),
+            (KeyCode::Char('a'), Action::Rotate180),
+            (KeyCode::Char('c'), Action::Hold),
+            (KeyCode::Char(' '), Action::Pause),
+            (KeyCode::Esc, Action::Exit),
+            (KeyCode::Char('e'), Action::Edit),
+            (KeyCode::Char(' '), Action::ToggleBlock),
+            (KeyCode::Char('f'), Action::FillLine),
+            (KeyCode::Char('u'), Action::Undo),
+            (KeyCode::Char('y'), Action::Redo),
+            (KeyCode::Char('r'), Action::Reset),
+            (KeyCode::Enter, Action::Confirm),
+            (KeyCode::Up, Action::Up),
+            (KeyCode::Down, Action::Down),
+            (KeyCode::Left, Action::Left),
+            (KeyCode::Right, Action::Right),
+            (KeyCode::Char('r'), Action::Replays),
+            (KeyCode::Char('c'), Action::Resume),
+            (KeyCode::Char('a'), Action::VersusBot),
+        ])
+    }
+}
+
+// the keys of the two players of a versus game, who share the keyboard
+impl KeyBindings {
+    pub fn versus_left() -> KeyBindings {
+        KeyBindings::new(vec![
+            (KeyCode::Char('a'), Action::MoveLeft),
+            (KeyCode::Char('d'), Action::MoveRight),
+            (KeyCode::Char('s'), Action::SoftDrop),
+            (KeyCode::Char(' '), Action::HardDrop),
+            (KeyCode::Char('w'), Action::RotateCw),
+            (KeyCode::Char('q'), Action::RotateCcw),
+            (KeyCode::Char('e'), Action::Hold),
+            (KeyCode::Esc, Action::Exit),
+        ])
+    }
+
+    pub fn versus_right() -> KeyBindings {
+        KeyBindings::new(vec![
+            (KeyCode::Left, Action::MoveLeft),
+            (KeyCode::Right, Action::MoveRight),
+            (KeyCode::Down, Action::SoftDrop),
+            (KeyCode::Enter, Action::HardDrop),
+            (KeyCode::Up, Action::RotateCw),
+            (KeyCode::Char('.'), Action::RotateCcw),
+            (KeyCode::Char('/'), Action::Hold),
+        ])
+    }
+}
+
+impl KeyBindings {
+    pub fn new(bindings: Vec<(KeyCode, Action)>) -> KeyBindings {
+        KeyBindings { bindings }
+    }
+
+    // presses and the terminal's own repeats, for menus
+    pub fn get_actions(&self, event: &Event) -> Vec<Action> {
+        self.find_actions(event, &[KeyEventKind::Press, KeyEventKind::Repeat])
+    }
+
+    // only the first press of a key, the game repeats moves itself
+    pub fn get_presses(&self, event: &Event) -> Vec<Action> {
+        self.find_actions(event, &[KeyEventKind::Press])
+    }
+
+    pub fn get_releases(&self, event: &Event) -> Vec<Action> {
+        self.find_actions(event, &[KeyEventKind::Release])
+    }
+
+    fn find_actions(&self, event: &Event, kinds: &[KeyEventKind]) -> Vec<Action> {
+        match event {
+            Event::Key(key) if kinds.contains(&key.kind) => self.bindings.iter()
+                .filter(|(code, _)| *code == key.code)
+                .map(|(_, action)| *action)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+// whether the flags asking for key releases were pushed to this terminal
+static KEY_RELEASES_PUSHED: AtomicBool = AtomicBool::new(false);
+
+// ask the terminal to report key releases, returns false if it cannot
+pub fn enable_key_releases() -> bool {
+    // the windows console always reports them
+    if cfg!(windows) {
+        return true;
+    }
+    let pushed = match supports_keyboard_enhancement() {
+        Ok(true) => execute!(stdout(), PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)).is_ok(),
+        _ => false,
+    };
+    KEY_RELEASES_PUSHED.store(pushed, Ordering::SeqCst);
+    pushed
+}
+
+// the terminal goes back to how it reported keys before enable_key_releases
+pub fn disable_key_releases() {
+    if KEY_RELEASES_PUSHED.swap(false, Ordering::SeqCst) {
+        let _ = execute!(stdout(), PopKeyboardEnhancementFlags);
+    }
+}
+
+// the events of a session of the server, read from its connection
+struct SessionEvents {
+    receiver: Receiver<Event>,
+    // the event a poll found, read next
+    next: Option<Event>,
+}
+
+thread_local! {
+    // every session runs on a thread of its own, the other threads read this terminal
+    static SESSION_EVENTS: RefCell<Option<SessionEvents>> = const { RefCell::new(None) };
+}
+
+// the screens of this thread read these events instead of the ones of this terminal
+pub fn read_from(receiver: Receiver<Event>) {
+    SESSION_EVENTS.with(|events| *events.borrow_mut() = Some(SessionEvents { receiver, next: None }));
+}
+
+// once its connection is closed the screens of a session are left with this error
+fn session_ended() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::ConnectionAborted, "the session has ended")
+}
+
+// like crossterm's poll, for the terminal of this thread
+pub fn poll(timeout: Duration) -> std::io::Result<bool> {
+    SESSION_EVENTS.with(|events| match events.borrow_mut().as_mut() {
+        Some(SessionEvents { next: Some(_), .. }) => Ok(true),
+        Some(events) => match events.receiver.recv_timeout(timeout) {
+            Ok(event) => {
+                events.next = Some(event);
+                Ok(true)
+            }
+            Err(RecvTimeoutError::Timeout) => Ok(false),
+            Err(RecvTimeoutError::Disconnected) => Err(session_ended()),
+        },
+        None => event::poll(timeout),
+    })
+}
+
+// like crossterm's read, for the terminal of this thread
+pub fn read() -> std::io::Result<Event> {
+    SESSION_EVENTS.with(|events| match events.borrow_mut().as_mut() {
+        Some(events) => match events.next.take() {
+            Some(event) => Ok(event),
+            None => events.receiver.recv().map_err(|_| session_ended()),
+        },
+        None => event::read(),
+    })
+}
+
+// how held keys repeat, in frames, a soft drop falls this many times faster than gravity
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Handling {
+    pub das: u32,
+    pub arr: u32,
+    pub soft_drop_factor: u32,
+}
+
+// repeats held moves and soft drops, which needs key releases, without them
+// every press of the terminal's own key repeat is a single move
+pub struct AutoRepeat {
+    handling: Handling,
+    key_releases: bool,
+    soft_drop_interval: u32,
+    // an arr of zero moves this far at once
+    max_shift: u16,
+    // the held move with the frame of its next repeat
+    shift: Option<(Action, u32)>,
+    soft_drop: Option<u32>,
+    // the actions whose keys are down
+    held: Vec<Action>,
+}
+
+impl AutoRepeat {
+    pub fn new(handling: Handling, key_releases: bool, soft_drop_interval: u32, max_shift: u16) -> AutoRepeat {
+        AutoRepeat {
+            handling,
+            key_releases,
+            soft_drop_interval: soft_drop_interval.max(1),
+            max_shift,
+            shift: None,
+            soft_drop: None,
+            held: Vec::new(),
+        }
+    }
+
+    // returns false for a key which is already down, the windows console reports the repeats
+    // of a held key as presses
+    pub fn press(&mut self, action: Action, frame: u32) -> bool {
+        if !self.key_releases {
+            return true;
+        }
+        if self.held.contains(&action) {
+            return false;
+        }
+        self.held.push(action);
+        match action {
+            // the last pressed direction wins
+            Action::MoveLeft | Action::MoveRight => self.shift = Some((action, frame + self.handling.das)),
+            Action::SoftDrop => self.soft_drop = Some(frame + self.soft_drop_interval),
+            _ => {}
+        }
+        true
+    }
+
+    pub fn release(&mut self, action: Action) {
+        self.held.retain(|held| *held != action);
+        match action {
+            Action::MoveLeft | Action::MoveRight if self.shift.map(|(held, _)| held) == Some(action) => self.shift = None,
+            Action::SoftDrop => self.soft_drop = None,
+            _ => {}
+        }
+    }
+
+    // forget the held keys, their releases may have been read elsewhere
+    pub fn clear(&mut self) {
+        self.shift = None;
+        self.soft_drop = None;
+        self.held.clear();
+    }
+
+    // the repeats of a frame, called once for every frame
+    pub fn take_due(&mut self, frame: u32) -> Vec<GameInput> {
+        let mut inputs = Vec::new();
+        if let Some((action, next_frame)) = self.shift.as_mut() {
+            if *next_frame <= frame {
+                let input = action.get_game_input().unwrap();
+                if self.handling.arr == 0 {
+                    // all the way to the wall, again in every frame for the next bricks
+                    inputs.extend((0..self.max_shift).map(|_| input));
+                } else {
+                    inputs.push(input);
+                    *next_frame += self.handling.arr;
+                }
+            }
+        }
+        if let Some(next_frame) = self.soft_drop.as_mut() {
+            if *next_frame <= frame {
+                inputs.push(GameInput::SoftDrop);
+                *next_frame += self.soft_drop_interval;
+            }
+        }
+        inputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+    use crate::gaming_screen::data::replay::GameInput;
+    use crate::input::*;
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn get_actions() {
+        let bindings = KeyBindings::default();
+        assert_eq!(bindings.get_actions(&key(KeyCode::Up)), vec![Action::RotateCw, Action::Up]);
+        assert_eq!(bindings.get_actions(&key(KeyCode::Char('q'))), vec![]);
+        assert_eq!(bindings.get_actions(&Event::Resize(10, 10)), vec![]);
+        assert_eq!(Action::HardDrop.get_game_input(), Some(GameInput::Drop));
+        assert_eq!(Action::Up.get_game_input(), None);
+    }
+
+    #[test]
+    fn presses_and_releases() {
+        let bindings = KeyBindings::default();
+        let mut release = KeyEvent::new(KeyCode::Left, KeyModifiers::NONE);
+        release.kind = KeyEventKind::Release;
+        let release = Event::Key(release);
+        assert_eq!(bindings.get_actions(&release), vec![]);
+        assert_eq!(bindings.get_presses(&release), vec![]);
+        assert_eq!(bindings.get_releases(&release), vec![Action::MoveLeft, Action::Left]);
+    }
+
+    static TEST_HANDLING: Handling = Handling {
+        das: 10,
+        arr: 2,
+        soft_drop_factor: 20,
+    };
+
+    fn take_frames(auto_repeat: &mut AutoRepeat, frames: std::ops::Range<u32>) -> Vec<GameInput> {
+        frames.flat_map(|frame| auto_repeat.take_due(frame)).collect()
+    }
+
+    #[test]
+    fn auto_repeat() {
+        let mut auto_repeat = AutoRepeat::new(TEST_HANDLING, true, 1, 10);
+        auto_repeat.press(Action::MoveLeft, 0);
+        assert_eq!(take_frames(&mut auto_repeat, 0..10), vec![]);
+        assert_eq!(take_frames(&mut auto_repeat, 10..15), vec![GameInput::Left; 3]);
+
+        // the last pressed direction wins, releasing the other one does not stop it
+        auto_repeat.press(Action::MoveRight, 15);
+        auto_repeat.release(Action::MoveLeft);
+        auto_repeat.press(Action::SoftDrop, 15);
+        assert_eq!(take_frames(&mut auto_repeat, 15..20), vec![GameInput::SoftDrop; 4]);
+        assert_eq!(auto_repeat.take_due(25), vec![GameInput::Right, GameInput::SoftDrop]);
+        auto_repeat.release(Action::MoveRight);
+        auto_repeat.release(Action::SoftDrop);
+        assert_eq!(take_frames(&mut auto_repeat, 26..100), vec![]);
+    }
+
+    #[test]
+    fn auto_repeat_without_releases() {
+        let mut auto_repeat = AutoRepeat::new(TEST_HANDLING, false, 1, 10);
+        assert!(auto_repeat.press(Action::MoveLeft, 0));
+        assert!(auto_repeat.press(Action::MoveLeft, 1));
+        assert_eq!(take_frames(&mut auto_repeat, 0..100), vec![]);
+    }
+
+    #[test]
+    fn repeated_presses() {
+        let mut auto_repeat = AutoRepeat::new(TEST_HANDLING, true, 1, 10);
+        assert!(auto_repeat.press(Action::MoveLeft, 0));
+        // the presses of a held key neither move again nor push the repeats back
+        for frame in 1..12 {
+            assert!(!auto_repeat.press(Action::MoveLeft, frame));
+            auto_repeat.take_due(frame);
+        }
+        assert_eq!(auto_repeat.take_due(12), vec![GameInput::Left]);
+        auto_repeat.release(Action::MoveLeft);
+        assert!(auto_repeat.press(Action::MoveLeft, 13));
+        assert!(auto_repeat.press(Action::RotateCw, 13));
+        assert!(!auto_repeat.press(Action::RotateCw, 14));
+    }
+
+    #[test]
+    fn session_events() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        // the thread of a test is the one of a session
+        read_from(receiver);
+        assert!(!poll(Duration::from_millis(1)).unwrap());
+        sender.send(key(KeyCode::Enter)).unwrap();
+        sender.send(Event::Resize(80, 24)).unwrap();
+        assert!(poll(Duration::from_millis(1)).unwrap());
+        assert!(poll(Duration::from_millis(1)).unwrap());
+        assert_eq!(read().unwrap(), key(KeyCode::Enter));
+        assert_eq!(read().unwrap(), Event::Resize(80, 24));
+        drop(sender);
+        assert_eq!(poll(Duration::from_millis(1)).unwrap_err().kind(), std::io::ErrorKind::ConnectionAborted);
+        assert!(read().is_err());
+    }
+
+    #[test]
+    fn zero_arr_moves_to_the_wall() {
+        let handling = Handling { arr: 0, ..TEST_HANDLING };
+        let mut auto_repeat = AutoRepeat::new(handling, true, 1, 10);
+        auto_repeat.press(Action::MoveRight, 0);
+        assert_eq!(take_frames(&mut auto_repeat, 0..10), vec![]);
+        assert_eq!(auto_repeat.take_due(10), vec![GameInput::Right; 10]);
+        auto_repeat.clear();
+        assert_eq!(auto_repeat.take_due(11), vec![]);
+    }
+}
@@ -1,76 +1,141 @@
 mod tetris;
+mod input;
 mod drawer;
 mod screens;
 mod welcome_screen;
 mod pause_screen;
 mod gaming_screen;
 mod player;
+mod puzzle_select_screen;
+mod replay_select_screen;
+mod theme;
+mod color;
+mod versus_screen;
+mod network;
+mod watch_screen;
+mod high_scores;
+mod server;
 
-use crate::gaming_screen::GamingScreen;
-use crate::pause_screen::PauseScreen;
-use crate::screens::{LoadScreen, NextScreen};
-use crate::welcome_screen::{WelcomeScreen};
-use crate::tetris::{AppSettings, Size, KeyboardControl};
-use crossterm::{
-    event::{KeyCode},
-};
+use crate::network::{Connection, Spectators};
+use crate::screens::Launch;
+use crate::tetris::AppSettings;
 use crate::drawer::CommandLineDrawer;
+use crate::theme::Theme;
+use crate::color::ColorSupport;
+use tetris_engine::data::versus::GarbageHoles;
+use std::net::TcpListener;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 
 fn main() {
     env_logger::init();
     log_panics::init();
-    let gaming_blocks_size = Size {
-        height: 20,
-        width: 10,
-    };
-    let gaming_region = Size {
-        height: gaming_blocks_size.height + 2,
-        width: gaming_blocks_size.width * 2 + 3, // width of ■ is 2
-    };
-    let info_region = Size {
-        height: gaming_region.height,
-        width: 20,
+    let args: Vec<String> = std::env::args().collect();
+    // the theme is chosen with --theme <name>
+    let theme = match args.iter().position(|arg| arg == "--theme") {
+        Some(index) => {
+            let name = args.get(index + 1).map(|name| name.as_str()).unwrap_or("");
+            Theme::find(name).unwrap_or_else(|error| {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            })
+        }
+        None => Theme::default(),
     };
-    let total_region = Size {
-        height: gaming_region.height,
-        width: info_region.width + gaming_region.width,
+    // --ascii draws with 7-bit characters only, which is the default where the locale is not utf-8
+    let theme = if args.iter().any(|arg| arg == "--ascii") || !theme::is_unicode_terminal() {
+        theme.to_ascii()
+    } else {
+        theme
     };
-    let keyboard_control = KeyboardControl {
-        exit: KeyCode::Esc,
-        change: KeyCode::Up,
-        down: KeyCode::Down,
-        pause: KeyCode::Char(' '),
-        start: KeyCode::Enter,
-        left: KeyCode::Left,
-        right: KeyCode::Right,
+    // colors the terminal cannot show are replaced by the nearest ones it can
+    let theme = theme.with_color_support(ColorSupport::detect());
+    // the holes of the garbage rows with --garbage-holes aligned|attack|row
+    let garbage_holes = match args.iter().position(|arg| arg == "--garbage-holes") {
+        Some(index) => {
+            let name = args.get(index + 1).map(|name| name.as_str()).unwrap_or("");
+            GarbageHoles::from_name(name).unwrap_or_else(|| {
+                eprintln!("unknown garbage holes \"{}\", the choices are aligned, attack and row", name);
+                std::process::exit(1);
+            })
+        }
+        None => GarbageHoles::PerAttack,
     };
-    let settings = &AppSettings {
-        gaming_region,
-        info_region,
-        total_region,
-        welcome_region: Size {
-            height: 22,
-            width: 52,
-        },
-        gaming_blocks_size,
-        keyboard_control,
+    let mut settings = AppSettings {
+        theme,
+        // --no-animations skips the line clear delay and the other effects
+        animations: !args.iter().any(|arg| arg == "--no-animations"),
+        garbage_holes,
+        ..AppSettings::default()
     };
 
-    let mut welcome_screen = WelcomeScreen {
-        settings
-    };
-    let mut pause_screen = PauseScreen {
-        settings
+    let flag_value = |flag: &str| args.iter().position(|arg| arg == flag).map(|index| args.get(index + 1).cloned().unwrap_or_default());
+    // --serve <port> plays a session on every terminal connecting to it instead of this one
+    if let Some(port) = flag_value("--serve") {
+        let listener = port.parse::<u16>().map_err(|_| format!("invalid port '{}'", port))
+            .and_then(|port| TcpListener::bind(("0.0.0.0", port)).map_err(|e| e.to_string()))
+            .unwrap_or_else(|error| {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            });
+        println!("Serving on port {}, connect with telnet <address> {}", port, port);
+        server::serve(listener, &settings);
+        return;
+    }
+
+    // --host <port> waits for another instance to play versus over the network, which joins it
+    // with --join <address>:<port>
+    let network_game = if let Some(port) = flag_value("--host") {
+        let listener = port.parse::<u16>().map_err(|_| format!("invalid port '{}'", port))
+            .and_then(|port| TcpListener::bind(("0.0.0.0", port)).map_err(|e| e.to_string()));
+        let seed = rand::random();
+        Some(listener.and_then(|listener| {
+            println!("Waiting for a player on port {}...", port);
+            Connection::host(&listener, seed).map(|connection| (connection, seed))
+        }))
+    } else {
+        flag_value("--join").map(|address| Connection::join(address.as_str()))
     };
-    let drawer = CommandLineDrawer::new();
-    let mut gaming_screen = GamingScreen::new(&settings, &drawer);
-    let mut next_screen: NextScreen;
-    next_screen = welcome_screen.load();
-    loop {
-        next_screen = match next_screen {
-            NextScreen::Welcome => welcome_screen.load(),
-            NextScreen::Gaming => gaming_screen.load(),
-            NextScreen::Pause => pause_screen.load()
-        }
+    let network_game = network_game.transpose().unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    });
+    // --spectate <port> shares the games played here, they are watched with --watch <address>:<port>
+    let spectators = flag_value("--spectate").map(|port| {
+        port.parse::<u16>().map_err(|_| format!("invalid port '{}'", port))
+            .and_then(|port| TcpListener::bind(("0.0.0.0", port)).map_err(|e| e.to_string()))
+            .map(Spectators::listen)
+    });
+    let spectators = spectators.transpose().unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    });
+    let watched_game = flag_value("--watch").map(|address| Connection::connect(address.as_str()))
+        .transpose()
+        .unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        });
+
+    // only this terminal is asked for key releases, the sessions of a server have their own
+    settings.key_releases = input::enable_key_releases();
+    let drawer = CommandLineDrawer::new(settings.theme.clone());
+    enable_raw_mode().unwrap();
+    let panic_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        panic_hook(info);
+    }));
+    // the screens only stop when this terminal cannot be read any more
+    if let Err(error) = screens::run(&settings, &drawer, Launch { network_game, watched_game, spectators, high_scores: None }) {
+        restore_terminal();
+        eprintln!("{}", error);
+        std::process::exit(1);
     }
+    restore_terminal();
+}
+
+// leave this terminal the way it was found
+fn restore_terminal() {
+    input::disable_key_releases();
+    let _ = disable_raw_mode();
 }
@@ -0,0 +1,475 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{channel, sync_channel, Receiver, SyncSender, TryRecvError, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+use tetris_engine::data::game_state::GameState;
+use crate::tetris::GameMode;
+
+// both players need the same version, it changes with the messages
+pub static PROTOCOL_VERSION: u32 = 1;
+// how long the handshake and a silent player are waited for
+static TIMEOUT: Duration = Duration::from_secs(5);
+static PING_INTERVAL: Duration = Duration::from_secs(1);
+// the messages which wait for a slow connection, once they are this many the other side counts as gone
+static SEND_QUEUE_SIZE: usize = 64;
+// how often the spectators are checked while the game sends nothing, like when it is paused
+static SPECTATOR_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+// everything two players tell each other, one message per line
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Message {
+    // the first message of both sides with their protocol version
+    Hello(u32),
+    // the host picks the seed, so both players get the same bricks
+    Start(u64),
+    // the mode of the game spectators see, sent before its first state
+    Mode(GameMode),
+    State(GameState),
+    Garbage(u16),
+    GameOver,
+    Ping(u32),
+    Pong(u32),
+    // the other player left, with the reason
+    Bye(String),
+}
+
+impl Message {
+    pub fn to_text(&self) -> String {
+        match self {
+            Message::Hello(version) => format!("hello {}", version),
+            Message::Start(seed) => format!("start {}", seed),
+            Message::Mode(mode) => format!("mode {}", mode.name()),
+            Message::State(state) => format!("state {}", state.to_text()),
+            Message::Garbage(rows) => format!("garbage {}", rows),
+            Message::GameOver => String::from("game_over"),
+            Message::Ping(id) => format!("ping {}", id),
+            Message::Pong(id) => format!("pong {}", id),
+            Message::Bye(reason) => format!("bye {}", reason),
+        }
+    }
+
+    pub fn parse(line: &str) -> Result<Message, String> {
+        let line = line.trim();
+        let (name, value) = match line.find(' ') {
+            Some(index) => (&line[..index], line[index + 1..].trim()),
+            None => (line, ""),
+        };
+        let parse_number = |value: &str| value.parse::<u64>().map_err(|_| format!("invalid number '{}'", value));
+        Ok(match name {
+            "hello" => Message::Hello(parse_number(value)? as u32),
+            "start" => Message::Start(parse_number(value)?),
+            "mode" => Message::Mode(GameMode::from_name(value).ok_or(format!("unknown mode '{}'", value))?),
+            "state" => Message::State(GameState::parse(value)?),
+            "garbage" => Message::Garbage(parse_number(value)? as u16),
+            "game_over" => Message::GameOver,
+            "ping" => Message::Ping(parse_number(value)? as u32),
+            "pong" => Message::Pong(parse_number(value)? as u32),
+            "bye" => Message::Bye(value.to_string()),
+            _ => return Err(format!("unknown message '{}'", line)),
+        })
+    }
+}
+
+// a versus game with another instance, the messages are read and written on threads of their
+// own, so a slow or stalled connection never holds up the game
+pub struct Connection {
+    stream: TcpStream,
+    messages: Receiver<Message>,
+    outgoing: SyncSender<String>,
+    last_received: Instant,
+    // the ping waiting for its pong
+    ping: Option<(u32, Instant)>,
+    next_ping_id: u32,
+    last_ping: Instant,
+    latency: Option<Duration>,
+}
+
+impl Connection {
+    // wait for a player to join and hand them the seed
+    pub fn host(listener: &TcpListener, seed: u64) -> Result<Connection, String> {
+        let (stream, _) = listener.accept().map_err(|e| e.to_string())?;
+        let mut connection = Connection::accept(stream)?;
+        connection.send(&Message::Start(seed))?;
+        Ok(connection)
+    }
+
+    // answer the hello of a player or spectator who connected
+    pub fn accept(stream: TcpStream) -> Result<Connection, String> {
+        let mut reader = Connection::open(&stream)?;
+        Connection::check_hello(&mut reader)?;
+        let mut connection = Connection::new(stream, reader)?;
+        connection.send(&Message::Hello(PROTOCOL_VERSION))?;
+        Ok(connection)
+    }
+
+    // join the game of a host, returns the seed of the game
+    pub fn join(address: impl ToSocketAddrs) -> Result<(Connection, u64), String> {
+        let (stream, mut reader) = Connection::say_hello(address)?;
+        let seed = match Connection::read_message(&mut reader)? {
+            Message::Start(seed) => seed,
+            message => return Err(format!("expected the start of the game, found '{}'", message.to_text())),
+        };
+        Ok((Connection::new(stream, reader)?, seed))
+    }
+
+    // watch the game of another instance
+    pub fn connect(address: impl ToSocketAddrs) -> Result<Connection, String> {
+        let (stream, reader) = Connection::say_hello(address)?;
+        Connection::new(stream, reader)
+    }
+
+    fn say_hello(address: impl ToSocketAddrs) -> Result<(TcpStream, BufReader<TcpStream>), String> {
+        let address = address.to_socket_addrs().map_err(|e| e.to_string())?
+            .next()
+            .ok_or("the address was not found")?;
+        let mut stream = TcpStream::connect_timeout(&address, TIMEOUT).map_err(|e| e.to_string())?;
+        let mut reader = Connection::open(&stream)?;
+        writeln!(stream, "{}", Message::Hello(PROTOCOL_VERSION).to_text()).map_err(|e| e.to_string())?;
+        Connection::check_hello(&mut reader)?;
+        Ok((stream, reader))
+    }
+
+    fn open(stream: &TcpStream) -> Result<BufReader<TcpStream>, String> {
+        stream.set_nodelay(true).map_err(|e| e.to_string())?;
+        stream.set_read_timeout(Some(TIMEOUT)).map_err(|e| e.to_string())?;
+        stream.set_write_timeout(Some(TIMEOUT)).map_err(|e| e.to_string())?;
+        Ok(BufReader::new(stream.try_clone().map_err(|e| e.to_string())?))
+    }
+
+    fn read_message(reader: &mut BufReader<TcpStream>) -> Result<Message, String> {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => Err(String::from("the connection was closed")),
+            Ok(_) => Message::parse(&line),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    // the other side tells why it gives up, like a version it does not speak
+    fn check_hello(reader: &mut BufReader<TcpStream>) -> Result<(), String> {
+        match Connection::read_message(reader)? {
+            Message::Hello(version) if version == PROTOCOL_VERSION => Ok(()),
+            Message::Hello(version) => {
+                let reason = format!("protocol version {} is not {}", version, PROTOCOL_VERSION);
+                let _ = writeln!(reader.get_mut(), "{}", Message::Bye(reason.clone()).to_text());
+                Err(reason)
+            }
+            Message::Bye(reason) => Err(reason),
+            message => Err(format!("expected hello, found '{}'", message.to_text())),
+        }
+    }
+
+    fn new(stream: TcpStream, reader: BufReader<TcpStream>) -> Result<Connection, String> {
+        // the thread waits for lines as long as it takes, the game checks for silence itself
+        reader.get_ref().set_read_timeout(None).map_err(|e| e.to_string())?;
+        let (sender, messages) = channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let message = match line.map(|line| Message::parse(&line)) {
+                    Ok(Ok(message)) => message,
+                    // a line this version does not know is skipped
+                    Ok(Err(_)) => continue,
+                    Err(_) => break,
+                };
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+        let (outgoing, lines) = sync_channel::<String>(SEND_QUEUE_SIZE);
+        let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+        thread::spawn(move || {
+            // the lines left are written before the connection is closed, unless a write fails
+            // or takes longer than the timeout
+            for line in lines {
+                if writeln!(writer, "{}", line).is_err() {
+                    break;
+                }
+            }
+            // the reading thread stops as well, so the game hears of it
+            let _ = writer.shutdown(Shutdown::Both);
+        });
+        Ok(Connection {
+            stream,
+            messages,
+            outgoing,
+            last_received: Instant::now(),
+            ping: None,
+            next_ping_id: 0,
+            last_ping: Instant::now(),
+            latency: None,
+        })
+    }
+
+    // queues the message for the writing thread, fails at once if the other side stopped reading
+    pub fn send(&mut self, message: &Message) -> Result<(), String> {
+        self.outgoing.try_send(message.to_text()).map_err(|error| match error {
+            TrySendError::Full(_) => {
+                let _ = self.stream.shutdown(Shutdown::Both);
+                String::from("the other side does not keep up")
+            }
+            TrySendError::Disconnected(_) => String::from("the connection was closed"),
+        })
+    }
+
+    // the messages which came in since the last call, pings are answered here and fail when
+    // the other player is gone or silent for too long
+    pub fn receive(&mut self) -> Result<Vec<Message>, String> {
+        let mut messages = Vec::new();
+        loop {
+            match self.messages.try_recv() {
+                Ok(message) => {
+                    self.last_received = Instant::now();
+                    match message {
+                        Message::Ping(id) => self.send(&Message::Pong(id))?,
+                        Message::Pong(id) => {
+                            if let Some((ping_id, sent)) = self.ping {
+                                if ping_id == id {
+                                    self.latency = Some(sent.elapsed());
+                                    self.ping = None;
+                                }
+                            }
+                        }
+                        message => messages.push(message),
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    if messages.is_empty() {
+                        return Err(String::from("the connection was closed"));
+                    }
+                    break;
+                }
+            }
+        }
+        if self.last_received.elapsed() > TIMEOUT {
+            return Err(String::from("the other player does not answer"));
+        }
+        if self.last_ping.elapsed() >= PING_INTERVAL {
+            self.last_ping = Instant::now();
+            self.ping = Some((self.next_ping_id, Instant::now()));
+            self.send(&Message::Ping(self.next_ping_id))?;
+            self.next_ping_id = self.next_ping_id.wrapping_add(1);
+        }
+        Ok(messages)
+    }
+
+    // the round trip of the last ping
+    pub fn get_latency(&self) -> Option<Duration> {
+        self.latency
+    }
+}
+
+// the spectators with the mode each of them was told about
+type SpectatorList = Vec<(Connection, Option<GameMode>)>;
+
+// everyone watching the games of this instance
+pub struct Spectators {
+    connections: Arc<Mutex<SpectatorList>>,
+}
+
+impl Spectators {
+    // spectators join at any time, and are let go when they leave or stop answering
+    pub fn listen(listener: TcpListener) -> Spectators {
+        let connections = Arc::new(Mutex::new(Vec::new()));
+        let accepted = Arc::clone(&connections);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Ok(connection) = Connection::accept(stream) {
+                    accepted.lock().unwrap().push((connection, None));
+                }
+            }
+        });
+        let checked = Arc::clone(&connections);
+        thread::spawn(move || loop {
+            thread::sleep(SPECTATOR_CHECK_INTERVAL);
+            checked.lock().unwrap().retain_mut(|(connection, _)| connection.receive().is_ok());
+        });
+        Spectators { connections }
+    }
+
+    // sending only queues the messages, so the lock is never held for a write, and a spectator
+    // whose queue is full has stopped reading and is let go
+    pub fn broadcast(&self, mode: GameMode, state: &GameState) {
+        let state = Message::State(state.clone());
+        self.connections.lock().unwrap().retain_mut(|(connection, sent_mode)| {
+            if *sent_mode != Some(mode) {
+                *sent_mode = Some(mode);
+                if connection.send(&Message::Mode(mode)).is_err() {
+                    return false;
+                }
+            }
+            connection.send(&state).is_ok()
+        });
+    }
+
+    pub fn count(&self) -> usize {
+        self.connections.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use crate::network::*;
+    use crate::tetris::GameMode;
+
+    #[test]
+    fn to_text_and_parse() {
+        let messages = vec![
+            Message::Hello(PROTOCOL_VERSION),
+            Message::Start(1234),
+            Message::Mode(GameMode::Big),
+            Message::State(GameState::parse("300 2 1 T - ..../.TT./###.").unwrap()),
+            Message::Garbage(4),
+            Message::GameOver,
+            Message::Ping(7),
+            Message::Pong(7),
+            Message::Bye(String::from("the game was left")),
+        ];
+        for message in messages {
+            assert_eq!(Message::parse(&message.to_text()), Ok(message));
+        }
+        assert!(Message::parse("garbage x").is_err());
+        assert!(Message::parse("attack 4").is_err());
+    }
+
+    fn receive_until(connection: &mut Connection, count: usize) -> Vec<Message> {
+        let start = Instant::now();
+        let mut messages = Vec::new();
+        while messages.len() < count && start.elapsed() < Duration::from_secs(5) {
+            messages.extend(connection.receive().unwrap());
+            thread::sleep(Duration::from_millis(10));
+        }
+        messages
+    }
+
+    #[test]
+    fn loopback() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let host = thread::spawn(move || Connection::host(&listener, 42).unwrap());
+        let (mut guest, seed) = Connection::join(address).unwrap();
+        let mut host = host.join().unwrap();
+        assert_eq!(seed, 42);
+
+        host.send(&Message::Garbage(2)).unwrap();
+        host.send(&Message::GameOver).unwrap();
+        assert_eq!(receive_until(&mut guest, 2), vec![Message::Garbage(2), Message::GameOver]);
+
+        // pings are answered by the other side without showing up as messages
+        host.send(&Message::Ping(3)).unwrap();
+        host.send(&Message::Bye(String::from("done"))).unwrap();
+        assert_eq!(receive_until(&mut guest, 1), vec![Message::Bye(String::from("done"))]);
+        host.ping = Some((3, Instant::now()));
+        let start = Instant::now();
+        while host.get_latency().is_none() && start.elapsed() < Duration::from_secs(5) {
+            host.receive().unwrap();
+        }
+        assert!(host.get_latency().is_some());
+
+        drop(host);
+        let start = Instant::now();
+        let closed = loop {
+            if let Err(error) = guest.receive() {
+                break Some(error);
+            }
+            if start.elapsed() > Duration::from_secs(5) {
+                break None;
+            }
+        };
+        assert_eq!(closed, Some(String::from("the connection was closed")));
+    }
+
+    #[test]
+    fn stalled_peer() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut peer = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        writeln!(peer, "hello {}", PROTOCOL_VERSION).unwrap();
+        let mut connection = Connection::accept(listener.accept().unwrap().0).unwrap();
+        // the peer reads nothing, sending gives up once its buffers and the queue are full
+        let message = Message::Bye("x".repeat(100_000));
+        let start = Instant::now();
+        let error = loop {
+            if let Err(error) = connection.send(&message) {
+                break error;
+            }
+            assert!(start.elapsed() < Duration::from_secs(5), "sending blocked");
+        };
+        assert_eq!(error, "the other side does not keep up");
+        let start = Instant::now();
+        while connection.receive().is_ok() {
+            assert!(start.elapsed() < Duration::from_secs(5), "the connection was not closed");
+        }
+        drop(peer);
+    }
+
+    #[test]
+    fn version_mismatch() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let guest = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            writeln!(stream, "hello {}", PROTOCOL_VERSION + 1).unwrap();
+            let mut line = String::new();
+            BufReader::new(stream).read_line(&mut line).unwrap();
+            line
+        });
+        assert!(Connection::host(&listener, 42).is_err());
+        assert!(guest.join().unwrap().starts_with("bye protocol version"));
+    }
+
+    #[test]
+    fn spectators() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let spectators = Spectators::listen(listener);
+        let mut spectator = Connection::connect(address).unwrap();
+        let start = Instant::now();
+        while spectators.count() == 0 && start.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let state = GameState::parse("300 2 0 T - ..../.TT./###.").unwrap();
+        spectators.broadcast(GameMode::Classic, &state);
+        spectators.broadcast(GameMode::Classic, &state);
+        // the mode is only sent again when it changes
+        assert_eq!(receive_until(&mut spectator, 3), vec![
+            Message::Mode(GameMode::Classic),
+            Message::State(state.clone()),
+            Message::State(state),
+        ]);
+
+        drop(spectator);
+        let start = Instant::now();
+        while spectators.count() > 0 && start.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(spectators.count(), 0);
+    }
+
+    #[test]
+    fn stalled_spectator() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let spectators = Spectators::listen(listener);
+        let mut spectator = TcpStream::connect(address).unwrap();
+        writeln!(spectator, "hello {}", PROTOCOL_VERSION).unwrap();
+        let start = Instant::now();
+        while spectators.count() == 0 && start.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        // the spectator reads nothing, the game goes on and lets it go
+        let state = GameState::parse("300 2 0 T - ..../.TT./###.").unwrap();
+        let start = Instant::now();
+        while spectators.count() > 0 {
+            assert!(start.elapsed() < Duration::from_secs(5), "the spectator was kept");
+            spectators.broadcast(GameMode::Classic, &state);
+        }
+        drop(spectator);
+    }
+}
@@ -7,52 +7,114 @@ use std::time::{Duration};
 use screens::{NextScreen};
 
 use crossterm::{
-    event::{poll, read, Event},
+    event::{Event},
 };
+use crate::input::{poll, read, Action};
 
 pub struct PauseScreen<'a> {
     pub settings: &'a tetris::AppSettings,
+    pub drawer: &'a dyn Drawer,
 }
 
-impl screens::LoadScreen for PauseScreen<'_> {
-    fn load(&mut self) -> NextScreen {
-        let drawer = drawer::CommandLineDrawer::new();
+impl PauseScreen<'_> {
+    fn draw(&self, drawer: &dyn Drawer) {
         let window_size = tetris::Size {
             width: self.settings.welcome_region.width,
             height: self.settings.welcome_region.height,
         };
-        drawer.draw_region(0, 0, window_size.width, window_size.width, &String::from(" "));
-        drawer.draw_frame(0, 0, window_size.width, window_size.width);
+        drawer.resize(&window_size);
+        drawer.draw_region(0, 0, window_size.width, window_size.height, &String::from(" "));
+        drawer.draw_frame(0, 0, window_size.width, window_size.height);
 
         let title_content = [
-            &String::from("██████  ██████  ██  ██  ██████  ██████"),
-            &String::from("██  ██  ██  ██  ██  ██  ██      ██    "),
-            &String::from("██████  ██████  ██  ██  ██████  ██████"),
-            &String::from("██      ██  ██  ██  ██      ██  ██    "),
-            &String::from("██      ██  ██  ██████  ██████  ██████"),
-        ];
-
-        // width of title text in utf8
-        let title_width = 40;
-        let title_x = (window_size.width - 6 - title_width) / 2;
+            "██████  ██████  ██  ██  ██████  ██████",
+            "██  ██  ██  ██  ██  ██  ██      ██    ",
+            "██████  ██████  ██  ██  ██████  ██████",
+            "██      ██  ██  ██  ██      ██  ██    ",
+            "██      ██  ██  ██████  ██████  ██████",
+        ].map(|line| line.replace('█', &drawer.get_theme().banner.to_string()));
+
+        // width of title text, the banner glyph is one column wide
+        let title_width = 38;
+        let title_x = (window_size.width - title_width) / 2;
         let title_y = (window_size.height / 2 - 5) as usize;
         for i in 0..title_content.len() {
-            drawer.draw_string(title_x, (i + title_y) as u16, title_content[i], None);
+            drawer.draw_string(title_x, (i + title_y) as u16, &title_content[i], None);
         }
+    }
+}
 
+impl screens::LoadScreen for PauseScreen<'_> {
+    fn load(&mut self) -> std::io::Result<NextScreen> {
+        let drawer = self.drawer;
+        self.draw(drawer);
 
         let next_screen = loop {
-            if poll(Duration::from_millis(500)).unwrap() {
-                match read().unwrap() {
-                    Event::Key(event) => {
-                        if event.code == self.settings.keyboard_control.pause {
-                            break NextScreen::Gaming;
-                        }
-                    }
-                    _ => {}
+            drawer.flush();
+            if poll(Duration::from_millis(500))? {
+                let event = read()?;
+                if let Event::Resize(_, _) = event {
+                    self.draw(drawer);
+                }
+                let actions = self.settings.key_bindings.get_actions(&event);
+                if actions.contains(&Action::Pause) {
+                    break NextScreen::Gaming;
                 }
             } else {}
         };
-        next_screen
+        Ok(next_screen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawer::CaptureDrawer;
+    use crate::pause_screen::*;
+    use crate::tetris::AppSettings;
+    use crate::theme::Theme;
+
+    #[test]
+    fn draw() {
+        let settings = AppSettings::default();
+        let drawer = CaptureDrawer::new(settings.welcome_region);
+        let screen = PauseScreen { settings: &settings, drawer: &drawer };
+        screen.draw(&drawer);
+        assert_eq!(drawer.get_lines(), vec![
+            "┏━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┓",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃      ██████  ██████  ██  ██  ██████  ██████      ┃",
+            "┃      ██  ██  ██  ██  ██  ██  ██      ██          ┃",
+            "┃      ██████  ██████  ██  ██  ██████  ██████      ┃",
+            "┃      ██      ██  ██  ██  ██      ██  ██          ┃",
+            "┃      ██      ██  ██  ██████  ██████  ██████      ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┛",
+        ]);
+    }
+
+    #[test]
+    fn draw_ascii() {
+        let settings = AppSettings::default();
+        let drawer = CaptureDrawer::with_theme(settings.welcome_region, Theme::default().to_ascii());
+        let screen = PauseScreen { settings: &settings, drawer: &drawer };
+        screen.draw(&drawer);
+        let lines = drawer.get_lines();
+        assert!(lines.iter().all(|line| line.is_ascii() && line.len() == 52));
+        assert_eq!(lines[0], format!("+{}+", "-".repeat(50)));
+        assert_eq!(lines[6], "|      ######  ######  ##  ##  ######  ######      |");
+        assert_eq!(lines[10], "|      ##      ##  ##  ######  ######  ######      |");
     }
 }
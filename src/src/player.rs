@@ -28,6 +28,16 @@ impl Player {
             last_time: Instant::now(),
         }
     }
+    // plays nothing, for the sessions of a server
+    pub fn silent() -> Player {
+        Player {
+            stream_handle: None,
+            stream: None,
+            duration_limit: Duration::from_millis(500),
+            last_time: Instant::now(),
+        }
+    }
+
     pub fn play(&mut self, t: SoundTypes) {
         if self.check_limit().is_err() {
             ()
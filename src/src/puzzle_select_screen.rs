@@ -0,0 +1,133 @@
+use crate::tetris;
+use crate::drawer;
+use crate::screens;
+
+use drawer::Drawer;
+use std::collections::HashSet;
+use std::fs;
+use std::time::{Duration};
+use screens::{NextScreen};
+
+use crossterm::{
+    style::{Color},
+    event::{Event},
+};
+use crate::gaming_screen::data::puzzle::Puzzle;
+use crate::input::{poll, read, Action};
+
+// puzzles found in this folder are listed after the built-in ones
+static PUZZLE_FOLDER: &str = "puzzles";
+static PROGRESS_FILE: &str = "puzzle_progress.txt";
+static LIST_HEIGHT: usize = 14;
+
+pub struct PuzzleSelectScreen<'a> {
+    pub settings: &'a tetris::AppSettings,
+    drawer: &'a dyn Drawer,
+    pub puzzles: Vec<Puzzle>,
+    solved: HashSet<String>,
+    selected: usize,
+}
+
+impl PuzzleSelectScreen<'_> {
+    pub fn new<'a>(settings: &'a tetris::AppSettings, drawer: &'a dyn Drawer) -> PuzzleSelectScreen<'a> {
+        let mut puzzles = Puzzle::get_built_in();
+        puzzles.append(&mut load_puzzle_folder());
+        let solved = match fs::read_to_string(PROGRESS_FILE) {
+            Ok(content) => content.lines().map(|line| line.to_string()).collect(),
+            Err(_) => HashSet::new(),
+        };
+        PuzzleSelectScreen {
+            settings,
+            drawer,
+            puzzles,
+            solved,
+            selected: 0,
+        }
+    }
+
+    pub fn mark_solved(&mut self, index: usize) {
+        self.solved.insert(self.puzzles[index].name.clone());
+        let mut names: Vec<&String> = self.solved.iter().collect();
+        names.sort();
+        let content: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+        let _ = fs::write(PROGRESS_FILE, content.join("\n"));
+    }
+
+    fn draw(&self, drawer: &dyn Drawer) {
+        let window_size = self.settings.welcome_region;
+        drawer.resize(&window_size);
+        drawer.draw_region(0, 0, window_size.width, window_size.height, &String::from(" "));
+        drawer.draw_frame(0, 0, window_size.width, window_size.height);
+
+        let title = String::from("Select a puzzle");
+        drawer.draw_string((window_size.width - title.len() as u16) / 2, 2, &title, None);
+        let help = String::from("Enter to play, Esc to go back");
+        drawer.draw_string((window_size.width - help.len() as u16) / 2, window_size.height - 3, &help, None);
+        self.draw_list(drawer);
+    }
+
+    fn draw_list(&self, drawer: &dyn Drawer) {
+        let window_size = self.settings.welcome_region;
+        let first = if self.selected < LIST_HEIGHT { 0 } else { self.selected + 1 - LIST_HEIGHT };
+        for row in 0..LIST_HEIGHT {
+            let index = first + row;
+            let text = match self.puzzles.get(index) {
+                Some(puzzle) => {
+                    let cursor = if index == self.selected { ">" } else { " " };
+                    let solved = if self.solved.contains(&puzzle.name) { "x" } else { " " };
+                    format!("{} [{}] {:<20} {:<13}", cursor, solved, puzzle.name, puzzle.goal.describe())
+                }
+                None => String::new(),
+            };
+            let text = format!("{:<44}", text);
+            let color = if index == self.selected { Some(Color::Yellow) } else { None };
+            drawer.draw_string((window_size.width - text.len() as u16) / 2, 4 + row as u16, &text, color);
+        }
+    }
+}
+
+fn load_puzzle_folder() -> Vec<Puzzle> {
+    let mut paths: Vec<_> = match fs::read_dir(PUZZLE_FOLDER) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    paths.iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .filter_map(|content| Puzzle::parse(&content).ok())
+        .collect()
+}
+
+impl screens::LoadScreen for PuzzleSelectScreen<'_> {
+    fn load(&mut self) -> std::io::Result<NextScreen> {
+        let drawer = self.drawer;
+        self.draw(drawer);
+
+        let settings = self.settings;
+        loop {
+            drawer.flush();
+            if poll(Duration::from_millis(500))? {
+                let event = read()?;
+                if let Event::Resize(_, _) = event {
+                    self.draw(drawer);
+                }
+                for action in settings.key_bindings.get_actions(&event) {
+                    match action {
+                        Action::Exit => return Ok(NextScreen::Welcome),
+                        Action::Confirm if !self.puzzles.is_empty() => return Ok(NextScreen::NewPuzzle(self.selected)),
+                        Action::Up if self.selected > 0 => {
+                            self.selected -= 1;
+                            self.draw_list(drawer);
+                        }
+                        Action::Down if self.selected + 1 < self.puzzles.len() => {
+                            self.selected += 1;
+                            self.draw_list(drawer);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::tetris;
+use crate::drawer;
+use crate::screens;
+
+use drawer::Drawer;
+use std::fs;
+use std::time::{Duration};
+use screens::{NextScreen};
+
+use crossterm::{
+    style::{Color},
+    event::{Event},
+};
+use crate::gaming_screen::data::replay::{format_duration, Replay, REPLAY_FOLDER};
+use crate::input::{poll, read, Action};
+
+static LIST_HEIGHT: usize = 14;
+
+pub struct ReplaySelectScreen<'a> {
+    pub settings: &'a tetris::AppSettings,
+    drawer: &'a dyn Drawer,
+    // file names with their replays, newest first
+    pub replays: Vec<(String, Replay)>,
+    selected: usize,
+}
+
+impl ReplaySelectScreen<'_> {
+    pub fn new<'a>(settings: &'a tetris::AppSettings, drawer: &'a dyn Drawer) -> ReplaySelectScreen<'a> {
+        ReplaySelectScreen {
+            settings,
+            drawer,
+            replays: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    fn draw(&self, drawer: &dyn Drawer) {
+        let window_size = self.settings.welcome_region;
+        drawer.resize(&window_size);
+        drawer.draw_region(0, 0, window_size.width, window_size.height, &String::from(" "));
+        drawer.draw_frame(0, 0, window_size.width, window_size.height);
+
+        let title = String::from("Select a replay");
+        drawer.draw_string((window_size.width - title.len() as u16) / 2, 2, &title, None);
+        let help = String::from("Enter to watch, Esc to go back");
+        drawer.draw_string((window_size.width - help.len() as u16) / 2, window_size.height - 3, &help, None);
+        self.draw_list(drawer);
+    }
+
+    fn draw_list(&self, drawer: &dyn Drawer) {
+        let window_size = self.settings.welcome_region;
+        let first = if self.selected < LIST_HEIGHT { 0 } else { self.selected + 1 - LIST_HEIGHT };
+        for row in 0..LIST_HEIGHT {
+            let index = first + row;
+            let text = match self.replays.get(index) {
+                Some((name, replay)) => {
+                    let cursor = if index == self.selected { ">" } else { " " };
+                    let duration = format_duration(replay.mode.tick_duration() * replay.ticks);
+                    format!("{} {:<24} {:<9} {}", cursor, name, replay.mode.name(), duration)
+                }
+                None => String::new(),
+            };
+            let text = format!("{:<44}", text);
+            let color = if index == self.selected { Some(Color::Yellow) } else { None };
+            drawer.draw_string((window_size.width - text.len() as u16) / 2, 4 + row as u16, &text, color);
+        }
+    }
+}
+
+fn load_replay_folder() -> Vec<(String, Replay)> {
+    let mut paths: Vec<_> = match fs::read_dir(REPLAY_FOLDER) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    paths.reverse();
+    paths.iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().to_string();
+            let replay = Replay::parse(&fs::read_to_string(path).ok()?).ok()?;
+            Some((name, replay))
+        })
+        .collect()
+}
+
+impl screens::LoadScreen for ReplaySelectScreen<'_> {
+    fn load(&mut self) -> std::io::Result<NextScreen> {
+        // games may have been recorded since the last visit
+        self.replays = load_replay_folder();
+        self.selected = self.selected.min(self.replays.len().saturating_sub(1));
+
+        let drawer = self.drawer;
+        self.draw(drawer);
+
+        let settings = self.settings;
+        loop {
+            drawer.flush();
+            if poll(Duration::from_millis(500))? {
+                let event = read()?;
+                if let Event::Resize(_, _) = event {
+                    self.draw(drawer);
+                }
+                for action in settings.key_bindings.get_actions(&event) {
+                    match action {
+                        Action::Exit => return Ok(NextScreen::Welcome),
+                        Action::Confirm if !self.replays.is_empty() => return Ok(NextScreen::PlayReplay(self.selected)),
+                        Action::Up if self.selected > 0 => {
+                            self.selected -= 1;
+                            self.draw_list(drawer);
+                        }
+                        Action::Down if self.selected + 1 < self.replays.len() => {
+                            self.selected += 1;
+                            self.draw_list(drawer);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
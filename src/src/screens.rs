@@ -1,9 +1,134 @@
+use crate::drawer::Drawer;
+use crate::gaming_screen::GamingScreen;
+use crate::high_scores::HighScores;
+use crate::network::{Connection, Spectators};
+use crate::pause_screen::PauseScreen;
+use crate::puzzle_select_screen::PuzzleSelectScreen;
+use crate::replay_select_screen::ReplaySelectScreen;
+use crate::tetris::{AppSettings, GameMode};
+use crate::versus_screen::VersusScreen;
+use crate::watch_screen::WatchScreen;
+use crate::welcome_screen::WelcomeScreen;
+
 pub trait LoadScreen {
-    fn load(&mut self) -> NextScreen;
+    fn load(&mut self) -> std::io::Result<NextScreen>;
 }
 
 pub enum NextScreen {
     Welcome,
     Gaming,
+    NewGame(GameMode),
+    Continue,
     Pause,
-}
\ No newline at end of file
+    PuzzleSelect,
+    NewPuzzle(usize),
+    PuzzleSolved(usize),
+    Replays,
+    PlayReplay(usize),
+    Versus,
+    // versus against the computer
+    VersusBot,
+    // the game of --host or --join
+    NetworkVersus,
+    // the game of --watch
+    Watch,
+}
+
+// what the screens start with besides the settings
+#[derive(Default)]
+pub struct Launch {
+    // the game of --host or --join with its seed
+    pub network_game: Option<(Connection, u64)>,
+    // the game of --watch
+    pub watched_game: Option<Connection>,
+    // the games are shared with --spectate
+    pub spectators: Option<Spectators>,
+    // the table of a server with the name of the player of the session
+    pub high_scores: Option<(HighScores, String)>,
+}
+
+// goes from screen to screen on this terminal or the one of a session, until its events cannot
+// be read, like when the connection of a session is closed
+pub fn run(settings: &AppSettings, drawer: &dyn Drawer, launch: Launch) -> std::io::Result<()> {
+    let Launch { mut network_game, mut watched_game, spectators, high_scores } = launch;
+    let mut welcome_screen = WelcomeScreen {
+        settings,
+        drawer,
+        selected_mode: 0,
+    };
+    let mut pause_screen = PauseScreen {
+        settings,
+        drawer,
+    };
+    let mut puzzle_select_screen = PuzzleSelectScreen::new(settings, drawer);
+    let mut replay_select_screen = ReplaySelectScreen::new(settings, drawer);
+    let mut gaming_screen = GamingScreen::new(settings, drawer);
+    if let Some(spectators) = spectators {
+        gaming_screen.share_with(spectators);
+    }
+    if let Some((high_scores, name)) = high_scores {
+        gaming_screen.share_scores(high_scores, name);
+    }
+    let mut versus_screen = VersusScreen::new(settings, drawer);
+    let mut watch_screen = WatchScreen::new(settings, drawer);
+    let mut next_screen: NextScreen;
+    next_screen = if network_game.is_some() {
+        NextScreen::NetworkVersus
+    } else if watched_game.is_some() {
+        NextScreen::Watch
+    } else {
+        welcome_screen.load()?
+    };
+    loop {
+        next_screen = match next_screen {
+            NextScreen::Welcome => welcome_screen.load(),
+            NextScreen::Gaming => gaming_screen.load(),
+            NextScreen::NewGame(mode) => {
+                gaming_screen.new_game(mode);
+                gaming_screen.load()
+            }
+            NextScreen::Continue => {
+                match gaming_screen.resume_game() {
+                    Ok(_) => gaming_screen.load(),
+                    Err(_) => Ok(NextScreen::Welcome),
+                }
+            }
+            NextScreen::Pause => pause_screen.load(),
+            NextScreen::PuzzleSelect => puzzle_select_screen.load(),
+            NextScreen::NewPuzzle(index) => {
+                match gaming_screen.new_puzzle(index, &puzzle_select_screen.puzzles[index]) {
+                    Ok(_) => gaming_screen.load(),
+                    Err(_) => Ok(NextScreen::PuzzleSelect),
+                }
+            }
+            NextScreen::PuzzleSolved(index) => {
+                puzzle_select_screen.mark_solved(index);
+                puzzle_select_screen.load()
+            }
+            NextScreen::Replays => replay_select_screen.load(),
+            NextScreen::PlayReplay(index) => gaming_screen.play_replay(&replay_select_screen.replays[index].1),
+            NextScreen::Versus => {
+                versus_screen.new_game();
+                versus_screen.load()
+            }
+            NextScreen::VersusBot => {
+                versus_screen.new_bot_game();
+                versus_screen.load()
+            }
+            NextScreen::NetworkVersus => match network_game.take() {
+                Some((connection, seed)) => {
+                    versus_screen.new_network_game(connection, seed);
+                    versus_screen.load()
+                }
+                None => Ok(NextScreen::Welcome),
+            },
+            NextScreen::Watch => match watched_game.take() {
+                Some(connection) => {
+                    watch_screen.watch(connection);
+                    watch_screen.load()
+                }
+                None => Ok(NextScreen::Welcome),
+            },
+        }?;
+    }
+}
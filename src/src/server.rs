@@ -0,0 +1,283 @@
+use crate::drawer::CommandLineDrawer;
+use crate::high_scores::HighScores;
+use crate::input;
+use crate::screens::{self, Launch};
+use crate::tetris::{AppSettings, Size};
+
+use crossterm::{
+    execute,
+    cursor::{MoveTo, Show},
+    event::{Event, KeyCode, KeyEvent, KeyModifiers},
+    style::ResetColor,
+    terminal::{Clear, ClearType},
+};
+use std::io::{BufWriter, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// the telnet commands and options of a session
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const ECHO: u8 = 1;
+const SUPPRESS_GO_AHEAD: u8 = 3;
+const NAWS: u8 = 31;
+
+const ESCAPE: u8 = 0x1b;
+
+// the size of a terminal which does not tell it
+static DEFAULT_TERMINAL_SIZE: Size = Size { width: 80, height: 24 };
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum DecoderState {
+    Text,
+    // an enter may be followed by a line feed or a zero, which are part of it
+    CarriageReturn,
+    Escape,
+    Sequence,
+    Command,
+    Option,
+    Subnegotiation,
+    SubnegotiationCommand,
+}
+
+// turns what a terminal sends into events, a telnet client sends its commands and the size of
+// its terminal among the keys
+pub struct TerminalDecoder {
+    state: DecoderState,
+    subnegotiation: Vec<u8>,
+}
+
+impl TerminalDecoder {
+    pub fn new() -> TerminalDecoder {
+        TerminalDecoder {
+            state: DecoderState::Text,
+            subnegotiation: Vec::new(),
+        }
+    }
+
+    // an escape alone at the end is the escape key, terminals send a whole sequence at once
+    pub fn decode(&mut self, bytes: &[u8]) -> Vec<Event> {
+        let mut events = Vec::new();
+        for &byte in bytes {
+            self.decode_byte(byte, &mut events);
+        }
+        if self.state == DecoderState::Escape {
+            events.push(key(KeyCode::Esc));
+            self.state = DecoderState::Text;
+        }
+        events
+    }
+
+    fn decode_byte(&mut self, byte: u8, events: &mut Vec<Event>) {
+        self.state = match self.state {
+            DecoderState::Text => return self.decode_text(byte, events),
+            DecoderState::CarriageReturn if byte == b'\n' || byte == 0 => DecoderState::Text,
+            DecoderState::CarriageReturn => return self.decode_text(byte, events),
+            DecoderState::Escape if byte == b'[' || byte == b'O' => DecoderState::Sequence,
+            DecoderState::Escape => {
+                events.push(key(KeyCode::Esc));
+                return self.decode_text(byte, events);
+            }
+            // the parameters of a sequence end with its final byte
+            DecoderState::Sequence if (0x40..=0x7e).contains(&byte) => {
+                let code = match byte {
+                    b'A' => Some(KeyCode::Up),
+                    b'B' => Some(KeyCode::Down),
+                    b'C' => Some(KeyCode::Right),
+                    b'D' => Some(KeyCode::Left),
+                    _ => None,
+                };
+                events.extend(code.map(key));
+                DecoderState::Text
+            }
+            DecoderState::Sequence => DecoderState::Sequence,
+            DecoderState::Command => match byte {
+                WILL | WONT | DO | DONT => DecoderState::Option,
+                SB => {
+                    self.subnegotiation.clear();
+                    DecoderState::Subnegotiation
+                }
+                _ => DecoderState::Text,
+            },
+            // the answers to the options of the session are not checked
+            DecoderState::Option => DecoderState::Text,
+            DecoderState::Subnegotiation if byte == IAC => DecoderState::SubnegotiationCommand,
+            DecoderState::Subnegotiation => {
+                self.subnegotiation.push(byte);
+                DecoderState::Subnegotiation
+            }
+            DecoderState::SubnegotiationCommand if byte == SE => {
+                if let [NAWS, width_high, width_low, height_high, height_low] = self.subnegotiation[..] {
+                    let width = u16::from_be_bytes([width_high, width_low]);
+                    let height = u16::from_be_bytes([height_high, height_low]);
+                    events.push(Event::Resize(width, height));
+                }
+                DecoderState::Text
+            }
+            // a doubled command byte is the byte itself
+            DecoderState::SubnegotiationCommand => {
+                self.subnegotiation.push(byte);
+                DecoderState::Subnegotiation
+            }
+        };
+    }
+
+    fn decode_text(&mut self, byte: u8, events: &mut Vec<Event>) {
+        self.state = DecoderState::Text;
+        let code = match byte {
+            IAC => {
+                self.state = DecoderState::Command;
+                return;
+            }
+            ESCAPE => {
+                self.state = DecoderState::Escape;
+                return;
+            }
+            b'\r' => {
+                self.state = DecoderState::CarriageReturn;
+                KeyCode::Enter
+            }
+            b'\n' => KeyCode::Enter,
+            b'\t' => KeyCode::Tab,
+            0x7f | 0x08 => KeyCode::Backspace,
+            0x01..=0x1a => {
+                let letter = (byte - 1 + b'a') as char;
+                events.push(Event::Key(KeyEvent::new(KeyCode::Char(letter), KeyModifiers::CONTROL)));
+                return;
+            }
+            0x20..=0x7e => KeyCode::Char(byte as char),
+            // the rest of utf-8 is not bound to anything
+            _ => return,
+        };
+        events.push(key(code));
+    }
+}
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+// every connection plays on a session of its own, the sessions share their high scores
+pub fn serve(listener: TcpListener, settings: &AppSettings) {
+    let high_scores = HighScores::new();
+    for (index, stream) in listener.incoming().filter_map(Result::ok).enumerate() {
+        // the terminal at the other end reports no key releases, the sounds would be heard here
+        // and the saved game would be the one of every session
+        let settings = AppSettings { key_releases: false, sounds: false, saves: false, ..settings.clone() };
+        let high_scores = high_scores.clone();
+        let name = format!("player {}", index + 1);
+        thread::spawn(move || {
+            let _ = run_session(stream, settings, high_scores, name);
+        });
+    }
+}
+
+// the screens of one player on the terminal at the other end, until it leaves
+fn run_session(stream: TcpStream, settings: AppSettings, high_scores: HighScores, name: String) -> std::io::Result<()> {
+    stream.set_nodelay(true)?;
+    let mut out = BufWriter::new(stream.try_clone()?);
+    // a telnet client sends every key as it is pressed, echoes none of them and tells its size
+    out.write_all(&[IAC, WILL, ECHO, IAC, WILL, SUPPRESS_GO_AHEAD, IAC, DO, NAWS])?;
+    out.flush()?;
+    let terminal_size = Arc::new(Mutex::new(DEFAULT_TERMINAL_SIZE));
+    let (sender, receiver) = mpsc::channel();
+    let reader_terminal_size = terminal_size.clone();
+    thread::spawn(move || read_session(stream, reader_terminal_size, sender));
+    input::read_from(receiver);
+    let drawer = CommandLineDrawer::with_writer(settings.theme.clone(), out, move || *terminal_size.lock().unwrap());
+    screens::run(&settings, &drawer, Launch { high_scores: Some((high_scores, name)), ..Launch::default() })
+}
+
+// the events of a session until its connection is closed or it is left with ctrl-c or ctrl-d
+fn read_session(mut stream: TcpStream, terminal_size: Arc<Mutex<Size>>, sender: Sender<Event>) {
+    let mut decoder = TerminalDecoder::new();
+    let mut buffer = [0; 256];
+    loop {
+        let count = match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => return,
+            Ok(count) => count,
+        };
+        for event in decoder.decode(&buffer[..count]) {
+            match event {
+                Event::Resize(width, height) => *terminal_size.lock().unwrap() = Size { width, height },
+                Event::Key(KeyEvent { code: KeyCode::Char('c') | KeyCode::Char('d'), modifiers: KeyModifiers::CONTROL, .. }) => {
+                    let _ = restore_terminal(&mut stream);
+                    let _ = stream.shutdown(Shutdown::Both);
+                    return;
+                }
+                _ => {}
+            }
+            // the session has ended
+            if sender.send(event).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+// the colors, the screen and the cursor as the session found them
+fn restore_terminal(out: &mut impl Write) -> std::io::Result<()> {
+    execute!(out, ResetColor, Clear(ClearType::All), MoveTo(0, 0), Show)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn decode_keys() {
+        let mut decoder = TerminalDecoder::new();
+        assert_eq!(decoder.decode(b"a \x1b[A\x1bOD"), vec![
+            key(KeyCode::Char('a')), key(KeyCode::Char(' ')), key(KeyCode::Up), key(KeyCode::Left),
+        ]);
+        // every way a terminal sends enter
+        assert_eq!(decoder.decode(b"\r\0\r\n\n\r"), vec![key(KeyCode::Enter); 4]);
+        assert_eq!(decoder.decode(b"\nx"), vec![key(KeyCode::Char('x'))]);
+        assert_eq!(decoder.decode(b"\x1b"), vec![key(KeyCode::Esc)]);
+        assert_eq!(decoder.decode(b"\x1bs\x03"), vec![
+            key(KeyCode::Esc), key(KeyCode::Char('s')),
+            Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+        ]);
+    }
+
+    #[test]
+    fn decode_telnet_commands() {
+        let mut decoder = TerminalDecoder::new();
+        let mut bytes = vec![IAC, DO, ECHO, b'w', IAC, WILL, NAWS, IAC, SB, NAWS, 0, 120, 0, 40, IAC, SE];
+        assert_eq!(decoder.decode(&bytes), vec![key(KeyCode::Char('w')), Event::Resize(120, 40)]);
+        // a size of 255 doubles its byte, and a command may come in two parts
+        bytes = vec![IAC, SB, NAWS, 0, IAC, IAC, 0];
+        assert_eq!(decoder.decode(&bytes), vec![]);
+        assert_eq!(decoder.decode(&[30, IAC, SE]), vec![Event::Resize(255, 30)]);
+    }
+
+    #[test]
+    fn serve_sessions() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, &AppSettings::default()));
+
+        let mut client = TcpStream::connect(address).unwrap();
+        client.set_read_timeout(Some(Duration::from_millis(100))).unwrap();
+        client.write_all(&[IAC, WILL, NAWS, IAC, SB, NAWS, 0, 60, 0, 24, IAC, SE]).unwrap();
+        let mut output = Vec::new();
+        let mut buffer = [0; 4096];
+        let deadline = Instant::now() + Duration::from_secs(5);
+        // the welcome screen is drawn on the terminal of the client, the blanks are skipped
+        while !String::from_utf8_lossy(&output).contains("Enter") {
+            assert!(Instant::now() < deadline, "the session drew nothing");
+            if let Ok(count) = client.read(&mut buffer) {
+                output.extend_from_slice(&buffer[..count]);
+            }
+        }
+        assert!(output.starts_with(&[IAC, WILL, ECHO, IAC, WILL, SUPPRESS_GO_AHEAD, IAC, DO, NAWS]));
+    }
+}
@@ -1,48 +1,73 @@
-use crossterm::{
-    event::{KeyCode},
-};
+pub use tetris_engine::tetris::{GameMode, MasterTiming, Point, Size};
+use tetris_engine::data::versus::GarbageHoles;
+use crate::input::{Handling, KeyBindings};
+use crate::theme::Theme;
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-pub struct Size {
-    pub height: u16,
-    pub width: u16,
-}
-
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-pub struct Point {
-    pub x: u16,
-    pub y: u16,
-}
-
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-pub struct Movement {
-    pub x: i16,
-    pub y: i16,
-}
-
-impl Point {
-    pub fn new(x: u16, y: u16) -> Point {
-        Point { x, y }
-    }
-}
-
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-pub struct KeyboardControl {
-    pub start: KeyCode,
-    pub pause: KeyCode,
-    pub down: KeyCode,
-    pub right: KeyCode,
-    pub left: KeyCode,
-    pub change: KeyCode,
-    pub exit: KeyCode,
-}
-
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub struct AppSettings {
     pub gaming_region: Size,
     pub info_region: Size,
-    pub total_region: Size,
     pub welcome_region: Size,
     pub gaming_blocks_size: Size,
-    pub keyboard_control: KeyboardControl,
+    pub key_bindings: KeyBindings,
+    // the left and the right player of a versus game
+    pub versus_key_bindings: [KeyBindings; 2],
+    pub handling: Handling,
+    // whether the terminal reports key releases
+    pub key_releases: bool,
+    pub master_timing: MasterTiming,
+    pub theme: Theme,
+    // line clears, locks and hard drops are animated
+    pub animations: bool,
+    // where the holes of the garbage rows of a versus game are
+    pub garbage_holes: GarbageHoles,
+    // the sessions of a server play no sounds
+    pub sounds: bool,
+    // the sessions of a server share the folder of the server, they neither save, continue nor record games
+    pub saves: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> AppSettings {
+        let gaming_blocks_size = Size {
+            height: 20,
+            width: 10,
+        };
+        let gaming_region = Size {
+            height: gaming_blocks_size.height + 2,
+            width: gaming_blocks_size.width * 2 + 3, // a block is two columns wide
+        };
+        let info_region = Size {
+            height: gaming_region.height,
+            width: 20,
+        };
+        AppSettings {
+            gaming_region,
+            info_region,
+            welcome_region: Size {
+                height: 22,
+                width: 52,
+            },
+            gaming_blocks_size,
+            key_bindings: KeyBindings::default(),
+            versus_key_bindings: [KeyBindings::versus_left(), KeyBindings::versus_right()],
+            handling: Handling {
+                das: 10,
+                arr: 2,
+                soft_drop_factor: 20,
+            },
+            // found out when the terminal is set up
+            key_releases: false,
+            master_timing: MasterTiming {
+                are: 30,
+                line_clear_delay: 41,
+                lock_delay: 30,
+            },
+            theme: Theme::default(),
+            animations: true,
+            garbage_holes: GarbageHoles::PerAttack,
+            sounds: true,
+            saves: true,
+        }
+    }
 }
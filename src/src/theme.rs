@@ -0,0 +1,381 @@
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use crossterm::style::Color;
+use crate::color::{lighten, ColorSupport};
+use crate::drawer::get_text_width;
+use crate::gaming_screen::data::bricks::BrickType;
+
+// themes found in this folder can be chosen besides the built-in ones
+static THEME_FOLDER: &str = "themes";
+static BUILT_IN_THEMES: [&str; 5] = [
+    include_str!("../asset/themes/classic.txt"),
+    include_str!("../asset/themes/monochrome.txt"),
+    include_str!("../asset/themes/high_contrast.txt"),
+    include_str!("../asset/themes/ascii.txt"),
+    include_str!("../asset/themes/neon.txt"),
+];
+static BRICK_TYPE_COUNT: usize = 7;
+static ASCII_THEME: usize = 3;
+
+// the corners and the sides of a frame
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct FrameStyle {
+    pub top_left: char,
+    pub top_right: char,
+    pub bottom_left: char,
+    pub bottom_right: char,
+    pub horizontal: char,
+    pub vertical: char,
+}
+
+impl FrameStyle {
+    // a named style or its six characters, the corners clockwise from the top left then the sides
+    fn parse(value: &str) -> Result<FrameStyle, String> {
+        let chars: Vec<char> = match value {
+            "heavy" => "┏┓┛┗━┃".chars().collect(),
+            "light" => "┌┐┘└─│".chars().collect(),
+            "double" => "╔╗╝╚═║".chars().collect(),
+            "ascii" => "++++-|".chars().collect(),
+            _ => value.chars().collect(),
+        };
+        if chars.len() != 6 || chars.iter().any(|c| get_text_width(&c.to_string()) != 1) {
+            return Err(format!("unknown frame '{}'", value));
+        }
+        Ok(FrameStyle {
+            top_left: chars[0],
+            top_right: chars[1],
+            bottom_right: chars[2],
+            bottom_left: chars[3],
+            horizontal: chars[4],
+            vertical: chars[5],
+        })
+    }
+
+    fn is_ascii(&self) -> bool {
+        [self.top_left, self.top_right, self.bottom_left, self.bottom_right, self.horizontal, self.vertical]
+            .iter()
+            .all(|c| c.is_ascii())
+    }
+}
+
+// a theme file looks like this, colors are the names crossterm knows, #rrggbb,
+// an ansi value from 0 to 255 or none for the color of the terminal:
+//
+//   name: Classic
+//   block: "■"
+//   empty: "  "
+//   frame: heavy
+//   banner: "█"
+//   colors: I=cyan O=yellow T=magenta S=green Z=red J=blue L=dark_yellow
+//   stack: none
+//   text: none
+//   background: none
+//   title: blue green red
+//   shaded: false
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Theme {
+    pub name: String,
+    // a block and an empty cell, both two columns wide
+    pub block: String,
+    pub empty: String,
+    pub frame: FrameStyle,
+    // the big letters of the home and pause screens, one column wide
+    pub banner: char,
+    // by brick type, blocks which are not from a brick have the stack color
+    pub brick_colors: [Option<Color>; BRICK_TYPE_COUNT],
+    pub stack_color: Option<Color>,
+    pub text_color: Option<Color>,
+    pub background_color: Option<Color>,
+    // the title of the home screen cycles through them
+    pub title_colors: Vec<Color>,
+    // blocks with a lighter top half, where the terminal has more than 16 colors
+    pub shaded: bool,
+    pub color_support: ColorSupport,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::parse(BUILT_IN_THEMES[0]).unwrap()
+    }
+}
+
+impl Theme {
+    pub fn parse(content: &str) -> Result<Theme, String> {
+        let mut theme = Theme {
+            name: String::new(),
+            block: String::from("■"),
+            empty: String::from("  "),
+            frame: FrameStyle::parse("heavy")?,
+            banner: '█',
+            brick_colors: [None; BRICK_TYPE_COUNT],
+            stack_color: None,
+            text_color: None,
+            background_color: None,
+            title_colors: Vec::new(),
+            shaded: false,
+            color_support: ColorSupport::TrueColor,
+        };
+        for line in content.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            let (key, value) = match line.find(':') {
+                Some(index) => (line[..index].trim(), unquote(line[index + 1..].trim())),
+                None => return Err(format!("expected 'key: value', found '{}'", line)),
+            };
+            match key {
+                "name" => theme.name = value.to_string(),
+                "block" => theme.block = parse_cell(value)?,
+                "empty" => theme.empty = parse_cell(value)?,
+                "frame" => theme.frame = FrameStyle::parse(value)?,
+                "banner" => {
+                    let mut chars = value.chars();
+                    theme.banner = match (chars.next(), chars.next()) {
+                        (Some(c), None) if get_text_width(value) == 1 => c,
+                        _ => return Err(format!("'{}' is not one column wide", value)),
+                    };
+                }
+                "colors" => {
+                    for entry in value.split_whitespace() {
+                        let (letter, color) = entry.split_once('=').ok_or(format!("expected 'piece=color', found '{}'", entry))?;
+                        let mut letters = letter.chars();
+                        let brick_type = match (letters.next().and_then(BrickType::from_char), letters.next()) {
+                            (Some(brick_type), None) => brick_type,
+                            _ => return Err(format!("unknown piece '{}'", letter)),
+                        };
+                        theme.brick_colors[brick_type as usize] = parse_color(color)?;
+                    }
+                }
+                "stack" => theme.stack_color = parse_color(value)?,
+                "text" => theme.text_color = parse_color(value)?,
+                "background" => theme.background_color = parse_color(value)?,
+                "title" => {
+                    theme.title_colors = value.split_whitespace()
+                        .map(parse_color)
+                        .collect::<Result<Vec<_>, _>>()?
+                        .into_iter()
+                        .flatten()
+                        .collect();
+                }
+                "shaded" => {
+                    theme.shaded = value.parse().map_err(|_| format!("expected true or false, found '{}'", value))?;
+                }
+                _ => return Err(format!("unknown key '{}'", key)),
+            }
+        }
+        if theme.name.is_empty() {
+            return Err(String::from("missing name"));
+        }
+        Ok(theme)
+    }
+
+    pub fn get_built_in() -> Vec<Theme> {
+        BUILT_IN_THEMES.iter().map(|content| Theme::parse(content).unwrap()).collect()
+    }
+
+    // a built-in theme or one from the theme folder, the name is not case sensitive
+    pub fn find(name: &str) -> Result<Theme, String> {
+        let mut themes = Theme::get_built_in();
+        themes.append(&mut load_theme_folder());
+        let names: Vec<String> = themes.iter().map(|theme| theme.name.clone()).collect();
+        themes.into_iter()
+            .find(|theme| theme.name.eq_ignore_ascii_case(name))
+            .ok_or(format!("unknown theme '{}', the themes are: {}", name, names.join(", ")))
+    }
+
+    // whether every glyph is a 7-bit character, which any terminal draws at the expected width,
+    // shaded blocks are drawn with half blocks
+    pub fn is_ascii(&self) -> bool {
+        self.block.is_ascii() && self.empty.is_ascii() && self.frame.is_ascii() && self.banner.is_ascii() && !self.shaded
+    }
+
+    // the same colors with the glyphs of the ascii theme in place of the others
+    pub fn to_ascii(&self) -> Theme {
+        if self.is_ascii() {
+            return self.clone();
+        }
+        let ascii = Theme::parse(BUILT_IN_THEMES[ASCII_THEME]).unwrap();
+        Theme {
+            block: if self.block.is_ascii() { self.block.clone() } else { ascii.block },
+            empty: if self.empty.is_ascii() { self.empty.clone() } else { ascii.empty },
+            frame: if self.frame.is_ascii() { self.frame } else { ascii.frame },
+            banner: if self.banner.is_ascii() { self.banner } else { ascii.banner },
+            shaded: false,
+            ..self.clone()
+        }
+    }
+
+    // the colors downsampled to the ones the terminal can show
+    pub fn with_color_support(&self, color_support: ColorSupport) -> Theme {
+        let downsample = |color: Option<Color>| color.map(|color| color_support.downsample(color));
+        let mut brick_colors = self.brick_colors;
+        for color in brick_colors.iter_mut() {
+            *color = downsample(*color);
+        }
+        Theme {
+            brick_colors,
+            stack_color: downsample(self.stack_color),
+            text_color: downsample(self.text_color),
+            background_color: downsample(self.background_color),
+            title_colors: self.title_colors.iter().map(|color| color_support.downsample(*color)).collect(),
+            color_support,
+            ..self.clone()
+        }
+    }
+
+    // the lighter top half of a block, None if blocks are not shaded
+    pub fn get_shade(&self, color: Color) -> Option<Color> {
+        if self.shaded && self.color_support > ColorSupport::Basic {
+            Some(self.color_support.downsample(lighten(color)))
+        } else {
+            None
+        }
+    }
+
+    pub fn get_block_color(&self, kind: Option<BrickType>) -> Option<Color> {
+        match kind {
+            Some(brick_type) => self.brick_colors[brick_type as usize],
+            None => self.stack_color,
+        }
+    }
+}
+
+// whether the locale promises utf-8, otherwise the ascii glyphs are safer
+pub fn is_unicode_terminal() -> bool {
+    // the windows console draws unicode whatever the locale
+    if cfg!(windows) {
+        return true;
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty());
+    is_unicode_locale(locale.as_deref())
+}
+
+fn is_unicode_locale(locale: Option<&str>) -> bool {
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        }
+        None => false,
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    }
+}
+
+fn parse_cell(value: &str) -> Result<String, String> {
+    if get_text_width(value) != 2 {
+        return Err(format!("'{}' is not two columns wide", value));
+    }
+    Ok(value.to_string())
+}
+
+fn parse_color(value: &str) -> Result<Option<Color>, String> {
+    if value == "none" {
+        return Ok(None);
+    }
+    if let Ok(ansi_value) = value.parse::<u8>() {
+        return Ok(Some(Color::AnsiValue(ansi_value)));
+    }
+    if value.len() == 7 && value.starts_with('#') {
+        let channel = |index: usize| u8::from_str_radix(&value[index..index + 2], 16);
+        if let (Ok(r), Ok(g), Ok(b)) = (channel(1), channel(3), channel(5)) {
+            return Ok(Some(Color::Rgb { r, g, b }));
+        }
+    }
+    Color::try_from(value).map(Some).map_err(|_| format!("unknown color '{}'", value))
+}
+
+fn load_theme_folder() -> Vec<Theme> {
+    let mut paths: Vec<_> = match fs::read_dir(THEME_FOLDER) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    paths.iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .filter_map(|content| Theme::parse(&content).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Color;
+    use crate::gaming_screen::data::bricks::BrickType;
+    use crate::theme::*;
+
+    #[test]
+    fn built_in_themes() {
+        let names: Vec<String> = Theme::get_built_in().into_iter().map(|theme| theme.name).collect();
+        assert_eq!(names, vec!["Classic", "Monochrome", "High-Contrast", "ASCII", "Neon"]);
+        assert_eq!(Theme::find("ascii").unwrap().empty, " .");
+        assert!(Theme::find("missing").is_err());
+    }
+
+    #[test]
+    fn parse() {
+        let theme = Theme::parse("name: Test\nblock: \"[]\"\nframe: +-+-=!\ncolors: J=blue I=none\nstack: grey\ntitle: red none").unwrap();
+        assert_eq!(theme.block, "[]");
+        assert_eq!(theme.empty, "  ");
+        assert_eq!(theme.frame.bottom_right, '+');
+        assert_eq!(theme.frame.vertical, '!');
+        assert_eq!(theme.get_block_color(Some(BrickType::RL)), Some(Color::Blue));
+        assert_eq!(theme.get_block_color(Some(BrickType::I)), None);
+        assert_eq!(theme.get_block_color(None), Some(Color::Grey));
+        assert_eq!(theme.title_colors, vec![Color::Red]);
+
+        assert_eq!(Theme::parse("name: Test\nblock: #"), Err(String::from("'#' is not two columns wide")));
+        assert_eq!(Theme::parse("name: Test\ncolors: X=red"), Err(String::from("unknown piece 'X'")));
+        assert_eq!(Theme::parse("name: Test\ntext: pink"), Err(String::from("unknown color 'pink'")));
+        assert_eq!(Theme::parse("block: \"[]\""), Err(String::from("missing name")));
+        assert_eq!(Theme::parse("name: Test\nbanner: ##"), Err(String::from("'##' is not one column wide")));
+    }
+
+    #[test]
+    fn colors() {
+        let theme = Theme::parse("name: Test\ncolors: I=#ff8700 O=208\nshaded: true").unwrap();
+        let orange = Color::Rgb { r: 255, g: 135, b: 0 };
+        assert_eq!(theme.get_block_color(Some(BrickType::I)), Some(orange));
+        assert_eq!(theme.get_block_color(Some(BrickType::O)), Some(Color::AnsiValue(208)));
+        assert_eq!(theme.get_shade(orange), Some(Color::Rgb { r: 255, g: 195, b: 127 }));
+        assert_eq!(Theme::parse("name: Test\ntext: #12345g"), Err(String::from("unknown color '#12345g'")));
+        assert_eq!(Theme::parse("name: Test\nshaded: yes"), Err(String::from("expected true or false, found 'yes'")));
+
+        let theme_256 = theme.with_color_support(ColorSupport::Ansi256);
+        assert_eq!(theme_256.get_block_color(Some(BrickType::I)), Some(Color::AnsiValue(208)));
+        assert_eq!(theme_256.get_shade(orange), Some(Color::AnsiValue(216)));
+        // 16 colors are too few for shades
+        let theme_16 = theme.with_color_support(ColorSupport::Basic);
+        assert_eq!(theme_16.get_block_color(Some(BrickType::O)), Some(Color::DarkYellow));
+        assert_eq!(theme_16.get_shade(orange), None);
+    }
+
+    #[test]
+    fn to_ascii() {
+        let theme = Theme::find("high-contrast").unwrap();
+        assert!(!theme.is_ascii());
+        let ascii = theme.to_ascii();
+        assert!(ascii.is_ascii());
+        assert_eq!(ascii.block, "[]");
+        assert_eq!(ascii.banner, '#');
+        assert_eq!(ascii.frame.top_left, '+');
+        assert_eq!(ascii.text_color, theme.text_color);
+        // ascii glyphs of a theme are kept
+        assert_eq!(Theme::default().to_ascii().empty, "  ");
+    }
+
+    #[test]
+    fn unicode_locale() {
+        assert!(is_unicode_locale(Some("en_US.UTF-8")));
+        assert!(is_unicode_locale(Some("zh_CN.utf8")));
+        assert!(!is_unicode_locale(Some("C")));
+        assert!(!is_unicode_locale(Some("POSIX")));
+        assert!(!is_unicode_locale(None));
+    }
+}
@@ -0,0 +1,567 @@
+use crate::drawer::Drawer;
+use crate::gaming_screen::data::bricks::BrickCollection;
+use crate::gaming_screen::data::game_state::GameState;
+use crate::gaming_screen::data::replay::GameInput;
+use crate::gaming_screen::data::master::FRAMES_PER_SECOND;
+use crate::gaming_screen::BOT_INPUT_FRAMES;
+use crate::gaming_screen::view::blocks_view::BlocksRenderView;
+use crate::gaming_screen::view::game_view::GameView;
+use crate::gaming_screen::view::info_view::InfoView;
+use crate::input::{poll, read, Action, AutoRepeat, KeyBindings};
+use crate::network::{Connection, Message};
+use crate::player::{Player, SoundTypes};
+use crate::screens::{LoadScreen, NextScreen};
+use crate::tetris::{AppSettings, GameMode, Point, Size};
+use tetris_engine::bot::{Bot, DEFAULT_WEIGHTS};
+use tetris_engine::game::{GameCore, NextResult};
+
+use std::thread;
+use std::time::{Duration, Instant};
+use crossterm::event::{Event, KeyEvent, KeyEventKind};
+
+// frames simulated at once at most, like in a single player game
+static MAX_CATCH_UP_FRAMES: u32 = 10;
+// how long a player who topped out waits for the other one to top out too, which is a draw
+static DRAW_WAIT: Duration = Duration::from_secs(1);
+
+// one of the two players with their own board and keys
+struct VersusSide<'a> {
+    label: &'static str,
+    core: GameCore,
+    game_view: GameView<'a>,
+    info_view: InfoView<'a>,
+    key_bindings: &'a KeyBindings,
+    // the last state of a player on another instance
+    remote_state: Option<GameState>,
+    // the computer plays this side instead of the keys
+    bot: Option<Bot>,
+}
+
+impl VersusSide<'_> {
+    fn init(&self) {
+        self.game_view.init();
+        self.info_view.init();
+    }
+
+    fn render(&self) {
+        self.game_view.render_blocks(&self.core.manager.blocks);
+        let pending_rows = match (&self.remote_state, &self.core.versus) {
+            (Some(state), _) => state.pending_garbage,
+            (None, Some(versus)) => versus.get_pending_rows(),
+            (None, None) => 0,
+        };
+        self.game_view.render_garbage_meter(pending_rows);
+        self.info_view.render_data();
+    }
+
+    fn update_bricks(&mut self) {
+        self.info_view.update_next_brick(self.core.next_brick);
+        self.info_view.update_hold_brick(self.core.hold);
+    }
+
+    // returns whether the input changed the game
+    fn play_input(&mut self, input: GameInput) -> bool {
+        let before = (self.core.manager.current_brick, self.core.hold, self.core.can_hold);
+        self.core.apply_input(input);
+        if input == GameInput::Hold {
+            self.update_bricks();
+        }
+        (self.core.manager.current_brick, self.core.hold, self.core.can_hold) != before
+    }
+
+    // the game of the other instance is only shown
+    fn show_state(&mut self, state: GameState, bricks: &BrickCollection) -> Result<(), String> {
+        state.apply_to(&mut self.core.manager.blocks)?;
+        self.info_view.set_score(state.score as f64, state.lines);
+        self.info_view.update_next_brick(state.next_brick.map(|brick_type| bricks.get_new_one(brick_type, 0)));
+        self.info_view.update_hold_brick(state.hold.map(|brick_type| bricks.get_new_one(brick_type, 0)));
+        self.remote_state = Some(state);
+        Ok(())
+    }
+
+    fn get_state(&self) -> GameState {
+        let (score, lines) = self.info_view.get_score();
+        GameState::new(&self.core, score as u32, lines)
+    }
+
+    fn time_tick(&mut self) -> NextResult {
+        let result = self.core.next_frame();
+        if let NextResult::LineClear(count, _) = result {
+            self.info_view.add_lines(count);
+        }
+        if let NextResult::LineClear(_, _) | NextResult::NewBrickPutIn(_) = result {
+            self.update_bricks();
+            if let Some(bot) = self.bot.as_mut() {
+                bot.on_new_brick(&self.core);
+            }
+        }
+        result
+    }
+}
+
+// two players on one keyboard or on two instances, the one who tops out first loses
+pub struct VersusScreen<'a> {
+    settings: &'a AppSettings,
+    drawer: &'a dyn Drawer,
+    sides: [VersusSide<'a>; 2],
+    // where the game over message is centered
+    middle_x: u16,
+    info_points: [Point; 2],
+    // the second player plays on another instance
+    remote: Option<Connection>,
+    remote_over: bool,
+    bricks: BrickCollection,
+}
+
+impl VersusScreen<'_> {
+    pub fn new<'a>(settings: &'a AppSettings, drawer: &'a dyn Drawer) -> VersusScreen<'a> {
+        let labels = [" 1P ", " 2P "];
+        let sides = [0, 1].map(|i| VersusSide {
+            label: labels[i],
+            core: GameCore::new(settings.gaming_blocks_size),
+            game_view: GameView::new(Point::new(0, 0), settings.gaming_region, drawer),
+            info_view: InfoView::new(settings, drawer),
+            key_bindings: &settings.versus_key_bindings[i],
+            remote_state: None,
+            bot: None,
+        });
+        VersusScreen {
+            settings,
+            drawer,
+            sides,
+            middle_x: settings.gaming_region.width,
+            info_points: [Point::new(0, 0); 2],
+            remote: None,
+            remote_over: false,
+            bricks: BrickCollection::new(),
+        }
+    }
+
+    pub fn new_game(&mut self) {
+        self.remote = None;
+        for (side, key_bindings) in self.sides.iter_mut().zip(self.settings.versus_key_bindings.iter()) {
+            side.key_bindings = key_bindings;
+        }
+        self.set_bot(None);
+        self.start(rand::random());
+    }
+
+    // the player is on the left with the keys of a single player game, the computer on the right
+    pub fn new_bot_game(&mut self) {
+        self.remote = None;
+        self.sides[0].key_bindings = &self.settings.key_bindings;
+        self.set_bot(Some(Bot::new(DEFAULT_WEIGHTS)));
+        self.start(rand::random());
+    }
+
+    // the local player is on the left with the keys of a single player game
+    pub fn new_network_game(&mut self, connection: Connection, seed: u64) {
+        self.remote = Some(connection);
+        self.sides[0].key_bindings = &self.settings.key_bindings;
+        self.set_bot(None);
+        self.start(seed);
+    }
+
+    fn set_bot(&mut self, bot: Option<Bot>) {
+        self.sides[1].label = if bot.is_some() { " CPU " } else { " 2P " };
+        self.sides[1].bot = bot;
+    }
+
+    // both players get the same bricks
+    fn start(&mut self, seed: u64) {
+        let settings = self.settings;
+        self.remote_over = false;
+        for side in self.sides.iter_mut() {
+            side.remote_state = None;
+            side.core.start(GameMode::Versus, settings.gaming_blocks_size, settings.master_timing, seed);
+            if let Some(versus) = side.core.versus.as_mut() {
+                versus.holes = settings.garbage_holes;
+            }
+            side.info_view.reset();
+            side.update_bricks();
+            if let Some(bot) = side.bot.as_mut() {
+                bot.clear();
+            }
+        }
+    }
+
+    // the infos go outside of the games, or below them if the terminal is too narrow
+    fn init(&mut self) {
+        let drawer = self.drawer;
+        let gaming_region = self.settings.gaming_region;
+        let info_region = self.settings.info_region;
+        let terminal_size = drawer.terminal_size();
+        let below = terminal_size.width < (gaming_region.width + info_region.width) * 2
+            && terminal_size.height >= gaming_region.height + info_region.height;
+        let window_size = if below {
+            Size {
+                width: gaming_region.width * 2,
+                height: gaming_region.height + info_region.height,
+            }
+        } else {
+            Size {
+                width: (gaming_region.width + info_region.width) * 2,
+                height: gaming_region.height.max(info_region.height),
+            }
+        };
+        for (i, side) in self.sides.iter_mut().enumerate() {
+            let i = i as u16;
+            let (game_point, info_point) = if below {
+                let game_point = Point::new(gaming_region.width * i, 0);
+                (game_point, Point::new(game_point.x + (gaming_region.width - info_region.width) / 2, gaming_region.height))
+            } else {
+                // [info 1][game 1][game 2][info 2]
+                let game_point = Point::new(info_region.width + gaming_region.width * i, 0);
+                (game_point, Point::new((info_region.width + gaming_region.width * 2) * i, 0))
+            };
+            side.game_view.move_to(game_point);
+            side.info_view.move_to(info_point);
+            self.info_points[i as usize] = info_point;
+        }
+        self.middle_x = if below { gaming_region.width } else { info_region.width + gaming_region.width };
+        drawer.resize(&window_size);
+        drawer.draw_region(0, 0, window_size.width, window_size.height, &String::from(" "));
+        for (side, info_point) in self.sides.iter().zip(self.info_points.iter()) {
+            side.init();
+            // the label of the player goes on the frame of their info
+            drawer.draw_string(info_point.x + 1, info_point.y, &String::from(side.label), None);
+        }
+    }
+
+    fn render(&self) {
+        for side in self.sides.iter() {
+            side.render();
+        }
+        if let Some(connection) = &self.remote {
+            // the round trip to the other player goes on the bottom of their info
+            let latency_text = match connection.get_latency() {
+                Some(latency) => format!(" {:>4} ms ", latency.as_millis()),
+                None => String::from(" ---- ms "),
+            };
+            let point = self.info_points[1];
+            self.drawer.draw_string(point.x + 1, point.y + self.settings.info_region.height - 1, &latency_text, None);
+        }
+        self.drawer.flush();
+    }
+
+    // the board of the other player and the garbage they send, returns whether anything came in
+    fn receive_remote(&mut self) -> Result<bool, String> {
+        let messages = match self.remote.as_mut() {
+            Some(connection) => connection.receive()?,
+            None => return Ok(false),
+        };
+        let received = !messages.is_empty();
+        for message in messages {
+            match message {
+                Message::State(state) => self.sides[1].show_state(state, &self.bricks)?,
+                Message::Garbage(rows) => {
+                    if let Some(versus) = self.sides[0].core.versus.as_mut() {
+                        versus.receive(rows);
+                    }
+                }
+                Message::GameOver => self.remote_over = true,
+                Message::Bye(reason) => return Err(reason),
+                _ => {}
+            }
+        }
+        Ok(received)
+    }
+
+    // a lost connection shows up when receiving
+    fn send_remote(&mut self, message: Message) {
+        if let Some(connection) = self.remote.as_mut() {
+            let _ = connection.send(&message);
+        }
+    }
+
+    // the other player may have topped out as well before they heard of it
+    fn wait_for_remote_game_over(&mut self) {
+        let start = Instant::now();
+        while !self.remote_over && start.elapsed() < DRAW_WAIT {
+            if self.receive_remote().is_err() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    // a tick of both games, the garbage one player sends rises under the other one's bricks
+    fn time_tick(&mut self) -> [NextResult; 2] {
+        if self.remote.is_some() {
+            let result = self.sides[0].time_tick();
+            let outgoing = self.sides[0].core.versus.as_mut().map_or(0, |versus| versus.take_outgoing());
+            if outgoing > 0 {
+                self.send_remote(Message::Garbage(outgoing));
+            }
+            let remote_result = if self.remote_over { NextResult::GameOver } else { NextResult::Waiting };
+            return [result, remote_result];
+        }
+        let results = [self.sides[0].time_tick(), self.sides[1].time_tick()];
+        for i in 0..2 {
+            let outgoing = self.sides[i].core.versus.as_mut().map_or(0, |versus| versus.take_outgoing());
+            if let Some(versus) = self.sides[1 - i].core.versus.as_mut() {
+                versus.receive(outgoing);
+            }
+        }
+        results
+    }
+
+    // the game is over when either player tops out, both at once is a draw, a player alone at
+    // this keyboard wins or loses
+    fn get_result_text(results: [NextResult; 2], alone: bool) -> Option<&'static str> {
+        match (results[0] == NextResult::GameOver, results[1] == NextResult::GameOver, alone) {
+            (true, true, _) => Some("DRAW"),
+            (true, false, false) => Some("PLAYER 2 WINS"),
+            (false, true, false) => Some("PLAYER 1 WINS"),
+            (true, false, true) => Some("YOU LOSE"),
+            (false, true, true) => Some("YOU WIN"),
+            (false, false, _) => None,
+        }
+    }
+
+    // show a message between both games until any key is pressed
+    fn wait_for_any_key(&mut self, message: &str) -> std::io::Result<()> {
+        let text = format!(" {} ", message);
+        loop {
+            let x = self.middle_x.saturating_sub(text.len() as u16 / 2);
+            self.drawer.draw_string(x, self.settings.gaming_region.height / 2, &text, None);
+            self.drawer.flush();
+            if poll(Duration::from_millis(500))? {
+                match read()? {
+                    // a key released from the game does not count
+                    Event::Key(KeyEvent { kind: KeyEventKind::Press, .. }) => return Ok(()),
+                    Event::Resize(_, _) => {
+                        self.init();
+                        self.render();
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+impl LoadScreen for VersusScreen<'_> {
+    fn load(&mut self) -> std::io::Result<NextScreen> {
+        self.init();
+        self.render();
+        let mut player = if self.settings.sounds { Player::new() } else { Player::silent() };
+        player.play_repeat(SoundTypes::Gaming);
+
+        let settings = self.settings;
+        let frame_duration = Duration::from_secs(1) / FRAMES_PER_SECOND;
+        let frames_per_tick = GameMode::Versus.frames_per_tick();
+        let soft_drop_interval = frames_per_tick / settings.handling.soft_drop_factor;
+        let width = settings.gaming_blocks_size.width;
+        let mut auto_repeats = [0, 1].map(|_| AutoRepeat::new(settings.handling, settings.key_releases, soft_drop_interval, width));
+        let mut frame: u32 = 0;
+        let mut next_tick_frame = frames_per_tick;
+        let mut next_frame_time = Instant::now() + frame_duration;
+        let mut events = Vec::new();
+        let next_screen = 'game: loop {
+            while poll(next_frame_time.saturating_duration_since(Instant::now()))? {
+                events.push(read()?);
+            }
+            if Instant::now().saturating_duration_since(next_frame_time) > frame_duration * MAX_CATCH_UP_FRAMES {
+                next_frame_time = Instant::now();
+            }
+            let mut changed = false;
+            match self.receive_remote() {
+                Ok(received) => changed |= received,
+                Err(_) => {
+                    self.render();
+                    self.wait_for_any_key("CONNECTION LOST")?;
+                    break 'game NextScreen::Welcome;
+                }
+            }
+            // the other player is only shown
+            let local_sides = if self.remote.is_some() { 1 } else { 2 };
+            while next_frame_time <= Instant::now() {
+                next_frame_time += frame_duration;
+                frame += 1;
+                changed |= !events.is_empty();
+                for event in events.drain(..) {
+                    if let Event::Resize(_, _) = event {
+                        self.init();
+                    }
+                    for (side, auto_repeat) in self.sides.iter_mut().zip(auto_repeats.iter_mut()).take(local_sides) {
+                        // the keys of the player still leave a game against the computer
+                        if side.bot.is_some() {
+                            continue;
+                        }
+                        for action in side.key_bindings.get_releases(&event) {
+                            auto_repeat.release(action);
+                        }
+                        for action in side.key_bindings.get_presses(&event) {
+                            if let Some(input) = action.get_game_input() {
+                                if auto_repeat.press(action, frame) && side.play_input(input) && input.is_rotation() {
+                                    player.play(SoundTypes::Change);
+                                }
+                            }
+                            if action == Action::Exit {
+                                self.send_remote(Message::Bye(String::from("the other player left")));
+                                break 'game NextScreen::Welcome;
+                            }
+                        }
+                    }
+                }
+                for (side, auto_repeat) in self.sides.iter_mut().zip(auto_repeats.iter_mut()).take(local_sides) {
+                    for input in auto_repeat.take_due(frame) {
+                        side.play_input(input);
+                        changed = true;
+                    }
+                }
+                if frame % BOT_INPUT_FRAMES == 0 {
+                    for side in self.sides.iter_mut() {
+                        if let Some(input) = side.bot.as_mut().and_then(Bot::next_input) {
+                            side.play_input(input);
+                            changed = true;
+                        }
+                    }
+                }
+                if frame < next_tick_frame {
+                    continue;
+                }
+                next_tick_frame += frames_per_tick;
+                changed = true;
+                let mut results = self.time_tick();
+                for result in results.iter() {
+                    if let NextResult::LineClear(count, _) = result {
+                        player.play(SoundTypes::LineClean(*count));
+                    }
+                }
+                if self.remote.is_some() && results[0] == NextResult::GameOver {
+                    let state = self.sides[0].get_state();
+                    self.send_remote(Message::State(state));
+                    self.send_remote(Message::GameOver);
+                    self.wait_for_remote_game_over();
+                    if self.remote_over {
+                        results[1] = NextResult::GameOver;
+                    }
+                }
+                let alone = self.remote.is_some() || self.sides[1].bot.is_some();
+                if let Some(text) = VersusScreen::get_result_text(results, alone) {
+                    self.render();
+                    self.wait_for_any_key(text)?;
+                    break 'game NextScreen::Welcome;
+                }
+            }
+            if changed {
+                if self.remote.is_some() {
+                    let state = self.sides[0].get_state();
+                    self.send_remote(Message::State(state));
+                }
+                self.render();
+            }
+        };
+        // a network game is played once
+        self.remote = None;
+        Ok(next_screen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Color;
+    use crate::drawer::CaptureDrawer;
+    use crate::gaming_screen::data::replay::GameInput;
+    use std::net::TcpListener;
+    use crate::tetris::{AppSettings, Size};
+    use crate::versus_screen::*;
+
+    #[test]
+    fn layout() {
+        let settings = AppSettings::default();
+        let drawer = CaptureDrawer::new(Size { width: 86, height: 22 });
+        let mut screen = VersusScreen::new(&settings, &drawer);
+        screen.init();
+        let lines = drawer.get_lines();
+        assert_eq!(lines.len(), 22);
+        assert_eq!(lines[0], format!("┏ 1P {}┓┏{}┓┏{}┓┏ 2P {}┓", "━".repeat(14), "━".repeat(21), "━".repeat(21), "━".repeat(14)));
+
+        // a narrow terminal has the infos below the games
+        let drawer = CaptureDrawer::new(Size { width: 46, height: 44 });
+        let mut screen = VersusScreen::new(&settings, &drawer);
+        screen.init();
+        let lines = drawer.get_lines();
+        assert_eq!(lines.len(), 44);
+        assert_eq!(lines[22], format!(" ┏ 1P {}┓   ┏ 2P {}┓", "━".repeat(14), "━".repeat(14)));
+    }
+
+    #[test]
+    fn send_garbage() {
+        let settings = AppSettings::default();
+        let drawer = CaptureDrawer::new(Size { width: 86, height: 22 });
+        let mut screen = VersusScreen::new(&settings, &drawer);
+        screen.new_game();
+        screen.time_tick();
+        // four full rows under the first player's falling brick
+        let blocks = &mut screen.sides[0].core.manager.blocks;
+        blocks.set_region(Point::new(0, 16), Size { width: 10, height: 4 }, true);
+        screen.sides[0].play_input(GameInput::Drop);
+        let results = screen.time_tick();
+        assert!(matches!(results[0], NextResult::LineClear(4, _)));
+        assert_eq!(screen.sides[1].core.versus.as_ref().unwrap().get_pending_rows(), 4);
+        // the meter of the second player shows the garbage on its way
+        screen.init();
+        screen.render();
+        assert_eq!(drawer.get_color(44, 17), Some(Color::Red));
+        assert_eq!(drawer.get_color(44, 16), None);
+        assert_eq!(VersusScreen::get_result_text(results, false), None);
+        assert_eq!(VersusScreen::get_result_text([NextResult::Success, NextResult::GameOver], false), Some("PLAYER 1 WINS"));
+        assert_eq!(VersusScreen::get_result_text([NextResult::GameOver, NextResult::GameOver], false), Some("DRAW"));
+        assert_eq!(VersusScreen::get_result_text([NextResult::GameOver, NextResult::Waiting], true), Some("YOU LOSE"));
+    }
+
+    #[test]
+    fn bot_game() {
+        let settings = AppSettings::default();
+        let drawer = CaptureDrawer::new(Size { width: 86, height: 22 });
+        let mut screen = VersusScreen::new(&settings, &drawer);
+        screen.new_bot_game();
+        assert_eq!(screen.sides[1].label, " CPU ");
+        // the computer finds where its first brick goes as soon as it is put in
+        screen.time_tick();
+        let bot = screen.sides[1].bot.as_mut().unwrap();
+        let inputs: Vec<GameInput> = std::iter::from_fn(|| bot.next_input()).collect();
+        assert_eq!(inputs.last(), Some(&GameInput::Drop));
+        assert_eq!(VersusScreen::get_result_text([NextResult::Success, NextResult::GameOver], true), Some("YOU WIN"));
+
+        screen.new_game();
+        assert!(screen.sides[1].bot.is_none());
+        assert_eq!(screen.sides[1].label, " 2P ");
+    }
+
+    #[test]
+    fn network_game() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let host_connection = thread::spawn(move || Connection::host(&listener, 42).unwrap());
+        let (guest_connection, seed) = Connection::join(address).unwrap();
+        let settings = AppSettings::default();
+        let drawer = CaptureDrawer::new(Size { width: 86, height: 22 });
+        let mut host = VersusScreen::new(&settings, &drawer);
+        host.new_network_game(host_connection.join().unwrap(), 42);
+        let mut guest = VersusScreen::new(&settings, &drawer);
+        guest.new_network_game(guest_connection, seed);
+        assert_eq!(host.sides[0].core.next_brick, guest.sides[0].core.next_brick);
+
+        // a tetris of the host is sent to the guest with the board of the host
+        host.time_tick();
+        host.sides[0].core.manager.blocks.set_region(Point::new(0, 16), Size { width: 10, height: 4 }, true);
+        host.sides[0].play_input(GameInput::Drop);
+        let results = host.time_tick();
+        assert!(matches!(results[0], NextResult::LineClear(4, _)));
+        let state = host.sides[0].get_state();
+        host.send_remote(Message::State(state.clone()));
+        host.send_remote(Message::GameOver);
+        let start = Instant::now();
+        while !guest.remote_over && start.elapsed() < Duration::from_secs(5) {
+            guest.receive_remote().unwrap();
+        }
+        assert_eq!(guest.sides[0].core.versus.as_ref().unwrap().get_pending_rows(), 4);
+        assert_eq!(guest.sides[1].remote_state, Some(state));
+        assert_eq!(guest.sides[1].core.manager.blocks.blocks, host.sides[0].core.manager.blocks.blocks);
+        assert_eq!(guest.time_tick()[1], NextResult::GameOver);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::drawer::Drawer;
+use crate::gaming_screen::data::BlocksData;
+use crate::gaming_screen::data::bricks::BrickCollection;
+use crate::gaming_screen::data::game_state::GameState;
+use crate::gaming_screen::get_layout;
+use crate::gaming_screen::view::blocks_view::BlocksRenderView;
+use crate::gaming_screen::view::game_view::GameView;
+use crate::gaming_screen::view::info_view::InfoView;
+use crate::input::{poll, read, Action};
+use crate::network::{Connection, Message};
+use crate::screens::{LoadScreen, NextScreen};
+use crate::tetris::{AppSettings, GameMode, Point};
+
+use std::time::Duration;
+use crossterm::event::Event;
+
+// how often the game being watched is checked for news
+static RECEIVE_INTERVAL: Duration = Duration::from_millis(15);
+
+// the game of another instance started with --spectate, as it is played
+pub struct WatchScreen<'a> {
+    settings: &'a AppSettings,
+    drawer: &'a dyn Drawer,
+    game_view: GameView<'a>,
+    info_view: InfoView<'a>,
+    blocks: BlocksData,
+    mode: GameMode,
+    connection: Option<Connection>,
+    bricks: BrickCollection,
+}
+
+impl WatchScreen<'_> {
+    pub fn new<'a>(settings: &'a AppSettings, drawer: &'a dyn Drawer) -> WatchScreen<'a> {
+        WatchScreen {
+            settings,
+            drawer,
+            game_view: GameView::new(Point::new(0, 0), settings.gaming_region, drawer),
+            info_view: InfoView::new(settings, drawer),
+            blocks: BlocksData::new(settings.gaming_blocks_size),
+            mode: GameMode::Classic,
+            connection: None,
+            bricks: BrickCollection::new(),
+        }
+    }
+
+    pub fn watch(&mut self, connection: Connection) {
+        self.connection = Some(connection);
+    }
+
+    fn init(&mut self) {
+        let drawer = self.drawer;
+        let (window_size, info_point) = get_layout(self.settings, drawer.terminal_size());
+        drawer.resize(&window_size);
+        drawer.draw_region(0, 0, window_size.width, window_size.height, &String::from(" "));
+        self.info_view.move_to(info_point);
+        self.info_view.init();
+        self.game_view.init();
+        drawer.draw_string(info_point.x + 1, info_point.y, &format!(" {} ", self.mode.name()), None);
+    }
+
+    fn render(&self) {
+        self.game_view.render_blocks(&self.blocks);
+        self.info_view.render_data();
+        self.drawer.flush();
+    }
+
+    // a new game may be in another mode with a board of another size
+    fn set_mode(&mut self, mode: GameMode) {
+        self.mode = mode;
+        self.blocks = BlocksData::new(mode.get_blocks_size(self.settings.gaming_blocks_size));
+        self.game_view.set_block_scale(mode.block_scale());
+        self.info_view.reset();
+        self.init();
+    }
+
+    fn show_state(&mut self, state: GameState) -> Result<(), String> {
+        state.apply_to(&mut self.blocks)?;
+        self.info_view.set_score(state.score as f64, state.lines);
+        self.info_view.update_next_brick(state.next_brick.map(|brick_type| self.bricks.get_new_one(brick_type, 0)));
+        self.info_view.update_hold_brick(state.hold.map(|brick_type| self.bricks.get_new_one(brick_type, 0)));
+        Ok(())
+    }
+
+    // returns whether anything came in
+    fn receive(&mut self) -> Result<bool, String> {
+        let messages = match self.connection.as_mut() {
+            Some(connection) => connection.receive()?,
+            None => return Err(String::from("no game to watch")),
+        };
+        let received = !messages.is_empty();
+        for message in messages {
+            match message {
+                Message::Mode(mode) => self.set_mode(mode),
+                Message::State(state) => self.show_state(state)?,
+                Message::Bye(reason) => return Err(reason),
+                _ => {}
+            }
+        }
+        Ok(received)
+    }
+}
+
+impl LoadScreen for WatchScreen<'_> {
+    fn load(&mut self) -> std::io::Result<NextScreen> {
+        self.init();
+        self.render();
+        let next_screen = loop {
+            if poll(RECEIVE_INTERVAL)? {
+                let event = read()?;
+                if let Event::Resize(_, _) = event {
+                    self.init();
+                    self.render();
+                }
+                if self.settings.key_bindings.get_actions(&event).contains(&Action::Exit) {
+                    break NextScreen::Welcome;
+                }
+            }
+            match self.receive() {
+                Ok(true) => self.render(),
+                Ok(false) => {}
+                Err(_) => {
+                    let text = " CONNECTION LOST ";
+                    let region = self.settings.gaming_region;
+                    self.drawer.draw_string((region.width - text.len() as u16) / 2, region.height / 2, &String::from(text), None);
+                    self.drawer.flush();
+                    // any key goes back home
+                    loop {
+                        if let Event::Key(_) = read()? {
+                            break;
+                        }
+                    }
+                    break NextScreen::Welcome;
+                }
+            }
+        };
+        self.connection = None;
+        Ok(next_screen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawer::CaptureDrawer;
+    use crate::gaming_screen::data::game_state::GameState;
+    use crate::tetris::{AppSettings, GameMode, Size};
+    use crate::watch_screen::*;
+
+    #[test]
+    fn show_states() {
+        let settings = AppSettings::default();
+        let drawer = CaptureDrawer::new(Size { width: 43, height: 22 });
+        let mut screen = WatchScreen::new(&settings, &drawer);
+        screen.set_mode(GameMode::Big);
+        let mut rows = vec![String::from("#####"); 10];
+        rows[0] = String::from("T....");
+        let state = GameState { score: 700, lines: 3, pending_garbage: 0, next_brick: None, hold: None, rows };
+        screen.show_state(state).unwrap();
+        screen.render();
+        let lines = drawer.get_lines();
+        assert_eq!(lines[0], format!("┏{}┓┏ Big {}┓", "━".repeat(21), "━".repeat(13)));
+        // the big blocks are two rows high
+        assert_eq!(lines[1], format!("┃ ■■{}┃┃{}┃", " ".repeat(16), " ".repeat(18)));
+        assert_eq!(lines[3], format!("┃ {}┃┃{}┃", "■".repeat(10), " ".repeat(18)));
+        assert!(lines[9].contains("700"));
+        // the board of another mode does not fit
+        let state = GameState { score: 0, lines: 0, pending_garbage: 0, next_brick: None, hold: None, rows: vec![] };
+        assert!(screen.show_state(state).is_err());
+    }
+}
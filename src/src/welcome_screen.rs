@@ -5,85 +5,196 @@ use crate::screens;
 use drawer::Drawer;
 use std::time::{Duration};
 use screens::{NextScreen};
-use std::io::{stdout};
+use std::path::Path;
 
 use std::fs::File;
 use std::io::BufReader;
 use rodio::{Decoder, OutputStream, source::Source};
 
 use crossterm::{
-    execute,
-    style::{Color},
-    event::{poll, read, Event},
-    terminal::{SetTitle},
+    event::{Event},
 };
+use crate::input::{poll, read, Action};
 use crate::player::Player;
+use crate::gaming_screen::data::save::SAVE_FILE;
 
 pub struct WelcomeScreen<'a> {
     pub settings: &'a tetris::AppSettings,
+    pub drawer: &'a dyn Drawer,
+    pub selected_mode: usize,
 }
 
-impl screens::LoadScreen for WelcomeScreen<'_> {
-    fn load(&mut self) -> NextScreen {
+impl WelcomeScreen<'_> {
+    // a game left in the middle can be continued, the sessions of a server have none
+    fn can_resume(&self) -> bool {
+        self.settings.saves && Path::new(SAVE_FILE).exists()
+    }
 
+    fn draw_mode(&self, drawer: &dyn Drawer, y: u16) {
+        let window_width = self.settings.welcome_region.width;
+        let mode = tetris::GameMode::ALL[self.selected_mode];
+        let mode_text = format!("<  Mode: {:^9}  >", mode.name());
+        let mode_text_x = (window_width - mode_text.len() as u16) / 2;
+        drawer.draw_string(mode_text_x, y, &mode_text, None);
+        // versus can be played against the computer too
+        let bot_text = if mode == tetris::GameMode::Versus { "Press A to play against the computer" } else { "" };
+        drawer.draw_string(1, y + 1, &format!("{:^1$}", bot_text, (window_width - 2) as usize), None);
+    }
 
+    fn get_mode_text_y(&self) -> u16 {
+        self.settings.welcome_region.height / 2 + 7
+    }
 
-        let drawer = drawer::CommandLineDrawer::new();
+    // everything but the title, which changes its colors
+    fn draw(&self, drawer: &dyn Drawer, can_resume: bool) {
         let window_size = tetris::Size {
             width: self.settings.welcome_region.width,
             height: self.settings.welcome_region.height,
         };
         drawer.resize(&window_size);
-        drawer.draw_region(0, 0, window_size.width, window_size.width, &String::from(" "));
-        drawer.draw_frame(0, 0, window_size.width, window_size.width);
-
-        let mut stdout = stdout();
-        execute!(stdout,SetTitle("Newbe.Tetris by Justin Yu")).unwrap();
-
-        let title_content = [
-            &String::from("██████  ██████  ██████  ██████  ██████  ██████"),
-            &String::from("  ██    ██        ██    ██  ██    ██    ██"),
-            &String::from("  ██    ██████    ██    ██████    ██    ██████"),
-            &String::from("  ██    ██        ██    ██ ██     ██        ██"),
-            &String::from("  ██    ██████    ██    ██  ██  ██████  ██████"),
-        ];
-        let colors = [
-            Color::Blue,
-            Color::Green,
-            Color::Red
-        ];
-        let mut first_color = 0;
+        drawer.draw_region(0, 0, window_size.width, window_size.height, &String::from(" "));
+        drawer.draw_frame(0, 0, window_size.width, window_size.height);
 
         let start_text = &String::from("Press Enter to start game!");
         let start_text_x = (window_size.width - start_text.len() as u16) / 2;
         let start_text_y = window_size.height / 2 + 5;
 
         drawer.draw_string(start_text_x, start_text_y, start_text, None);
+        let mode_text_y = self.get_mode_text_y();
+        self.draw_mode(drawer, mode_text_y);
+        let hint_text = &String::from(if can_resume { "Press C to continue, R to watch replays" } else { "Press R to watch replays" });
+        drawer.draw_string((window_size.width - hint_text.len() as u16) / 2, mode_text_y + 2, hint_text, None);
+    }
+}
+
+impl screens::LoadScreen for WelcomeScreen<'_> {
+    fn load(&mut self) -> std::io::Result<NextScreen> {
+        let drawer = self.drawer;
+        let window_size = self.settings.welcome_region;
+        let can_resume = self.can_resume();
+        self.draw(drawer, can_resume);
+        drawer.set_title("Newbe.Tetris by Justin Yu");
+
+        let title_content = [
+            "██████  ██████  ██████  ██████  ██████  ██████",
+            "  ██    ██        ██    ██  ██    ██    ██",
+            "  ██    ██████    ██    ██████    ██    ██████",
+            "  ██    ██        ██    ██ ██     ██        ██",
+            "  ██    ██████    ██    ██  ██  ██████  ██████",
+        ].map(|line| line.replace('█', &drawer.get_theme().banner.to_string()));
+        let colors = &self.settings.theme.title_colors;
+        let mut first_color = 0;
+
+        let mode_text_y = self.get_mode_text_y();
 
-        // width of title text in utf8
+        // width of title text, the banner glyph is one column wide
         let title_width = 46;
         let title_x = (window_size.width - title_width) / 2;
         let title_y = (window_size.height / 2 - 5) as usize;
         let next_screen = loop {
-            if poll(Duration::from_millis(500)).unwrap() {
-                match read().unwrap() {
-                    Event::Key(event) => {
-                        if event.code == self.settings.keyboard_control.start {
-                            break NextScreen::Gaming;
+            drawer.flush();
+            if poll(Duration::from_millis(500))? {
+                let mode_count = tetris::GameMode::ALL.len();
+                let mut next_screen = None;
+                let event = read()?;
+                if let Event::Resize(_, _) = event {
+                    self.draw(drawer, can_resume);
+                }
+                for action in self.settings.key_bindings.get_actions(&event) {
+                    match action {
+                        Action::Confirm => {
+                            let mode = tetris::GameMode::ALL[self.selected_mode];
+                            next_screen = Some(match mode {
+                                tetris::GameMode::Puzzle => NextScreen::PuzzleSelect,
+                                tetris::GameMode::Versus => NextScreen::Versus,
+                                _ => NextScreen::NewGame(mode),
+                            });
+                        }
+                        Action::Resume if can_resume => next_screen = Some(NextScreen::Continue),
+                        Action::VersusBot if tetris::GameMode::ALL[self.selected_mode] == tetris::GameMode::Versus => {
+                            next_screen = Some(NextScreen::VersusBot);
                         }
+                        Action::Replays => next_screen = Some(NextScreen::Replays),
+                        Action::Left => {
+                            self.selected_mode = (self.selected_mode + mode_count - 1) % mode_count;
+                            self.draw_mode(drawer, mode_text_y);
+                        }
+                        Action::Right => {
+                            self.selected_mode = (self.selected_mode + 1) % mode_count;
+                            self.draw_mode(drawer, mode_text_y);
+                        }
+                        _ => {}
                     }
-                    _ => {}
+                }
+                if let Some(next_screen) = next_screen {
+                    break next_screen;
                 }
             } else {
-                first_color = (first_color + 1) % colors.len();
+                // a theme without title colors draws it in the text color
+                let color_count = colors.len().max(1);
+                first_color = (first_color + 1) % color_count;
                 let mut color_index = first_color;
                 for i in 0..title_content.len() {
-                    color_index = (color_index + 1) % colors.len();
-                    let color = colors[color_index];
-                    drawer.draw_string(title_x, (i + title_y) as u16, title_content[i], Some(color));
+                    color_index = (color_index + 1) % color_count;
+                    let color = colors.get(color_index).copied();
+                    drawer.draw_string(title_x, (i + title_y) as u16, &title_content[i], color);
                 }
             }
         };
-        next_screen
+        Ok(next_screen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawer::CaptureDrawer;
+    use crate::tetris::AppSettings;
+    use crate::welcome_screen::*;
+
+    #[test]
+    fn draw() {
+        let settings = AppSettings::default();
+        let drawer = CaptureDrawer::new(settings.welcome_region);
+        let screen = WelcomeScreen { settings: &settings, drawer: &drawer, selected_mode: 0 };
+        screen.draw(&drawer, true);
+        assert_eq!(drawer.get_lines(), vec![
+            "┏━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┓",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃                                                  ┃",
+            "┃            Press Enter to start game!            ┃",
+            "┃                                                  ┃",
+            "┃              <  Mode:  Classic   >               ┃",
+            "┃                                                  ┃",
+            "┃     Press C to continue, R to watch replays      ┃",
+            "┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┛",
+        ]);
+
+        // versus tells how to play against the computer
+        let versus = tetris::GameMode::ALL.iter().position(|&mode| mode == tetris::GameMode::Versus).unwrap();
+        let screen = WelcomeScreen { settings: &settings, drawer: &drawer, selected_mode: versus };
+        screen.draw_mode(&drawer, screen.get_mode_text_y());
+        assert_eq!(drawer.get_lines()[19], "┃       Press A to play against the computer       ┃");
+    }
+
+    #[test]
+    fn sessions_do_not_resume() {
+        let settings = AppSettings { saves: false, ..AppSettings::default() };
+        let drawer = CaptureDrawer::new(settings.welcome_region);
+        let screen = WelcomeScreen { settings: &settings, drawer: &drawer, selected_mode: 0 };
+        // whatever game the server left, a session is not offered it
+        assert!(!screen.can_resume());
     }
 }